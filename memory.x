@@ -1,5 +1,9 @@
 MEMORY
 {
+  /* sector 1 (16K at 0x08004000) holds the settings */
   FLASH : ORIGIN = 0x08000000, LENGTH = 256K
   RAM : ORIGIN = 0x20000000, LENGTH = 64K
 }
+
+/* only the vector table is in sector 0, the code starts at sector 2 */
+_stext = ORIGIN(FLASH) + 0x8000;
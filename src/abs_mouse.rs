@@ -0,0 +1,101 @@
+use {
+    usb_device::bus::{UsbBus, UsbBusAllocator},
+    usbd_human_interface_device::{
+        device::DeviceClass,
+        interface::{
+            InBytes8, Interface, InterfaceBuilder, InterfaceConfig, OutNone, ReportSingle,
+            UsbAllocatable,
+        },
+        UsbHidError,
+    },
+    stm32f4xx_hal::prelude::*,
+    yuki_core::warp::AbsReport,
+};
+
+// 3 buttons and x, y from 0 to 32767 over the whole screen
+#[rustfmt::skip]
+const ABS_MOUSE_REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01,       // Usage Page (Generic Desktop)
+    0x09, 0x02,       // Usage (Mouse)
+    0xA1, 0x01,       // Collection (Application)
+    0x09, 0x01,       //   Usage (Pointer)
+    0xA1, 0x00,       //   Collection (Physical)
+    0x05, 0x09,       //     Usage Page (Button)
+    0x19, 0x01,       //     Usage Minimum (1)
+    0x29, 0x03,       //     Usage Maximum (3)
+    0x15, 0x00,       //     Logical Minimum (0)
+    0x25, 0x01,       //     Logical Maximum (1)
+    0x75, 0x01,       //     Report Size (1)
+    0x95, 0x03,       //     Report Count (3)
+    0x81, 0x02,       //     Input (Data, Variable, Absolute)
+    0x75, 0x05,       //     Report Size (5)
+    0x95, 0x01,       //     Report Count (1)
+    0x81, 0x03,       //     Input (Constant), padding
+    0x05, 0x01,       //     Usage Page (Generic Desktop)
+    0x09, 0x30,       //     Usage (X)
+    0x09, 0x31,       //     Usage (Y)
+    0x15, 0x00,       //     Logical Minimum (0)
+    0x26, 0xFF, 0x7F, //     Logical Maximum (32767)
+    0x75, 0x10,       //     Report Size (16)
+    0x95, 0x02,       //     Report Count (2)
+    0x81, 0x02,       //     Input (Data, Variable, Absolute)
+    0xC0,             //   End Collection
+    0xC0,             // End Collection
+];
+
+/// The pointer the warp mode moves, next to the wheel mouse.
+pub struct AbsMouse<'a, B: UsbBus> {
+    interface: Interface<'a, B, InBytes8, OutNone, ReportSingle>,
+}
+
+impl<'a, B: UsbBus> AbsMouse<'a, B> {
+    pub fn write_report(&mut self, report: &AbsReport) -> Result<(), UsbHidError> {
+        let [x0, x1] = report.x.to_le_bytes();
+        let [y0, y1] = report.y.to_le_bytes();
+
+        self.interface
+            .write_report(&[report.buttons, x0, x1, y0, y1])
+            .map(|_| ())
+            .map_err(UsbHidError::from)
+    }
+}
+
+impl<'a, B: UsbBus> DeviceClass<'a> for AbsMouse<'a, B> {
+    type I = Interface<'a, B, InBytes8, OutNone, ReportSingle>;
+
+    fn interface(&mut self) -> &mut Self::I {
+        &mut self.interface
+    }
+
+    fn reset(&mut self) {}
+
+    fn tick(&mut self) -> Result<(), UsbHidError> {
+        Ok(())
+    }
+}
+
+pub struct AbsMouseConfig<'a> {
+    interface: InterfaceConfig<'a, InBytes8, OutNone, ReportSingle>,
+}
+
+impl<'a> Default for AbsMouseConfig<'a> {
+    fn default() -> Self {
+        Self {
+            interface: InterfaceBuilder::new(ABS_MOUSE_REPORT_DESCRIPTOR)
+                .unwrap()
+                .description("Absolute Pointer")
+                .in_endpoint(10.millis())
+                .unwrap()
+                .without_out_endpoint()
+                .build(),
+        }
+    }
+}
+
+impl<'a, B: UsbBus + 'a> UsbAllocatable<'a, B> for AbsMouseConfig<'a> {
+    type Allocated = AbsMouse<'a, B>;
+
+    fn allocate(self, usb_alloc: &'a UsbBusAllocator<B>) -> Self::Allocated {
+        AbsMouse { interface: Interface::new(usb_alloc, self.interface) }
+    }
+}
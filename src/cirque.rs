@@ -0,0 +1,73 @@
+use {
+    stm32f4xx_hal::hal::i2c::I2c,
+    yuki_core::pointing::Sensor,
+};
+
+const ADDRESS: u8 = 0x2A;
+
+// register access protocol, the register goes in the low bits
+const READ: u8 = 0xA0;
+const WRITE: u8 = 0x80;
+
+const STATUS1: u8 = 0x02;
+const SYS_CONFIG1: u8 = 0x03;
+const FEED_CONFIG1: u8 = 0x04;
+const FEED_CONFIG2: u8 = 0x05;
+const PACKET_BYTE0: u8 = 0x12;
+
+// data ready
+const SW_DR: u8 = 1 << 2;
+// feed on, relative data
+const FEED_RELATIVE: u8 = 0x01;
+// taps, secondary tap, scroll and glide extend off, just the motion
+const FEED_MOTION_ONLY: u8 = 0x1E;
+
+/// Cirque Pinnacle trackpad over I2C, in relative mode.
+pub struct Cirque<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C: I2c> Cirque<I2C> {
+    pub fn new(i2c: I2C) -> Result<Self, I2C::Error> {
+        let mut c = Self { i2c };
+        c.write(STATUS1, 0)?;
+        c.write(SYS_CONFIG1, 0)?;
+        c.write(FEED_CONFIG2, FEED_MOTION_ONLY)?;
+        c.write(FEED_CONFIG1, FEED_RELATIVE)?;
+        Ok(c)
+    }
+
+    fn read_motion(&mut self) -> Result<(i16, i16), I2C::Error> {
+        let mut status = [0];
+        self.read(STATUS1, &mut status)?;
+        if status[0] & SW_DR == 0 {
+            return Ok((0, 0));
+        }
+
+        let mut packet = [0; 3];
+        self.read(PACKET_BYTE0, &mut packet)?;
+        // the next packet only comes once the flags are cleared
+        self.write(STATUS1, 0)?;
+
+        // 9 bit deltas, the sign bits are in the first byte
+        let delta = |low: u8, sign: bool| low as i16 - if sign { 256 } else { 0 };
+        Ok((
+            delta(packet[1], packet[0] & 1 << 4 != 0),
+            delta(packet[2], packet[0] & 1 << 5 != 0),
+        ))
+    }
+
+    fn read(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), I2C::Error> {
+        self.i2c.write_read(ADDRESS, &[READ | reg], buf)
+    }
+
+    fn write(&mut self, reg: u8, value: u8) -> Result<(), I2C::Error> {
+        self.i2c.write(ADDRESS, &[WRITE | reg, value])
+    }
+}
+
+impl<I2C: I2c> Sensor for Cirque<I2C> {
+    fn motion(&mut self) -> Result<(i16, i16), ()> {
+        self.read_motion().map_err(|_| ())
+    }
+}
@@ -0,0 +1,40 @@
+use stm32f4xx_hal as hal;
+
+use {
+    hal::{
+        flash::FlashExt,
+        pac::FLASH,
+    },
+    yuki_core::settings::Storage,
+};
+
+// the 16K sector 1 is skipped by the code in memory.x, erasing one of the
+// 128K sectors would stall the cpu for a second or two
+const SECTOR: u8 = 1;
+const OFFSET: usize = 0x4000;
+const SECTOR_LEN: usize = 0x4000;
+
+/// The settings sector of the flash.
+pub struct SettingsFlash {
+    flash: FLASH,
+}
+
+impl SettingsFlash {
+    pub fn new(flash: FLASH) -> Self {
+        Self { flash }
+    }
+}
+
+impl Storage for SettingsFlash {
+    fn read(&self) -> &[u8] {
+        &self.flash.read()[OFFSET..OFFSET + SECTOR_LEN]
+    }
+
+    fn erase(&mut self) -> Result<(), ()> {
+        self.flash.unlocked().erase(SECTOR).map_err(|_| ())
+    }
+
+    fn program(&mut self, offset: usize, bytes: &[u8]) -> Result<(), ()> {
+        self.flash.unlocked().program(OFFSET + offset, bytes.iter()).map_err(|_| ())
+    }
+}
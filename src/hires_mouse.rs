@@ -0,0 +1,164 @@
+use {
+    usb_device::{bus::{UsbBus, UsbBusAllocator}, UsbError},
+    usbd_human_interface_device::{
+        device::{mouse::WheelMouseReport, DeviceClass},
+        interface::{
+            InBytes8, Interface, InterfaceBuilder, InterfaceConfig, OutBytes8, ReportSingle,
+            UsbAllocatable,
+        },
+        descriptor::InterfaceProtocol,
+        UsbHidError,
+    },
+    stm32f4xx_hal::prelude::*,
+};
+
+// the wheel mouse of usbd-human-interface-device with a resolution
+// multiplier feature for each wheel, the host sets it to 16 wheel units
+// per notch when it supports hi-res scrolling
+#[rustfmt::skip]
+const HIRES_MOUSE_REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01,       // Usage Page (Generic Desktop)
+    0x09, 0x02,       // Usage (Mouse)
+    0xA1, 0x01,       // Collection (Application)
+    0x09, 0x01,       //   Usage (Pointer)
+    0xA1, 0x00,       //   Collection (Physical)
+    0x05, 0x09,       //     Usage Page (Button)
+    0x19, 0x01,       //     Usage Minimum (1)
+    0x29, 0x08,       //     Usage Maximum (8)
+    0x15, 0x00,       //     Logical Minimum (0)
+    0x25, 0x01,       //     Logical Maximum (1)
+    0x75, 0x01,       //     Report Size (1)
+    0x95, 0x08,       //     Report Count (8)
+    0x81, 0x02,       //     Input (Data, Variable, Absolute)
+    0x05, 0x01,       //     Usage Page (Generic Desktop)
+    0x09, 0x30,       //     Usage (X)
+    0x09, 0x31,       //     Usage (Y)
+    0x15, 0x81,       //     Logical Minimum (-127)
+    0x25, 0x7F,       //     Logical Maximum (127)
+    0x75, 0x08,       //     Report Size (8)
+    0x95, 0x02,       //     Report Count (2)
+    0x81, 0x06,       //     Input (Data, Variable, Relative)
+    0xA1, 0x02,       //     Collection (Logical)
+    0x09, 0x48,       //       Usage (Resolution Multiplier)
+    0x15, 0x00,       //       Logical Minimum (0)
+    0x25, 0x01,       //       Logical Maximum (1)
+    0x35, 0x01,       //       Physical Minimum (1)
+    0x45, 0x10,       //       Physical Maximum (16)
+    0x75, 0x02,       //       Report Size (2)
+    0x95, 0x01,       //       Report Count (1)
+    0xB1, 0x02,       //       Feature (Data, Variable, Absolute)
+    0x09, 0x38,       //       Usage (Wheel)
+    0x15, 0x81,       //       Logical Minimum (-127)
+    0x25, 0x7F,       //       Logical Maximum (127)
+    0x35, 0x00,       //       Physical Minimum (0)
+    0x45, 0x00,       //       Physical Maximum (0)
+    0x75, 0x08,       //       Report Size (8)
+    0x81, 0x06,       //       Input (Data, Variable, Relative)
+    0xC0,             //     End Collection
+    0xA1, 0x02,       //     Collection (Logical)
+    0x09, 0x48,       //       Usage (Resolution Multiplier)
+    0x15, 0x00,       //       Logical Minimum (0)
+    0x25, 0x01,       //       Logical Maximum (1)
+    0x35, 0x01,       //       Physical Minimum (1)
+    0x45, 0x10,       //       Physical Maximum (16)
+    0x75, 0x02,       //       Report Size (2)
+    0xB1, 0x02,       //       Feature (Data, Variable, Absolute)
+    0x35, 0x00,       //       Physical Minimum (0)
+    0x45, 0x00,       //       Physical Maximum (0)
+    0x75, 0x04,       //       Report Size (4)
+    0xB1, 0x03,       //       Feature (Constant), padding
+    0x05, 0x0C,       //       Usage Page (Consumer)
+    0x0A, 0x38, 0x02, //       Usage (AC Pan)
+    0x15, 0x81,       //       Logical Minimum (-127)
+    0x25, 0x7F,       //       Logical Maximum (127)
+    0x75, 0x08,       //       Report Size (8)
+    0x81, 0x06,       //       Input (Data, Variable, Relative)
+    0xC0,             //     End Collection
+    0xC0,             //   End Collection
+    0xC0,             // End Collection
+];
+
+const HIRES_MULTIPLIER: u8 = 16;
+
+pub struct HiResMouse<'a, B: UsbBus> {
+    interface: Interface<'a, B, InBytes8, OutBytes8, ReportSingle>,
+    multiplier: u8,
+}
+
+impl<'a, B: UsbBus> HiResMouse<'a, B> {
+    pub fn write_report(&mut self, report: &WheelMouseReport) -> Result<(), UsbHidError> {
+        let data = [
+            report.buttons,
+            report.x as u8,
+            report.y as u8,
+            report.vertical_wheel as u8,
+            report.horizontal_wheel as u8,
+        ];
+
+        self.interface
+            .write_report(&data)
+            .map(|_| ())
+            .map_err(UsbHidError::from)
+    }
+
+    /// Wheel units per notch, 1 until the host sets the multiplier
+    /// feature (and again after a usb reset).
+    pub fn multiplier(&mut self) -> u8 {
+        let mut data = [0; 1];
+        match self.interface.read_report(&mut data) {
+            Ok(1) => {
+                self.multiplier = match data[0] & 1 {
+                    1 => HIRES_MULTIPLIER,
+                    _ => 1,
+                }
+            },
+            Ok(_) | Err(UsbError::WouldBlock) => (),
+            Err(_) => self.multiplier = 1,
+        }
+
+        self.multiplier
+    }
+}
+
+impl<'a, B: UsbBus> DeviceClass<'a> for HiResMouse<'a, B> {
+    type I = Interface<'a, B, InBytes8, OutBytes8, ReportSingle>;
+
+    fn interface(&mut self) -> &mut Self::I {
+        &mut self.interface
+    }
+
+    fn reset(&mut self) {
+        self.multiplier = 1;
+    }
+
+    fn tick(&mut self) -> Result<(), UsbHidError> {
+        Ok(())
+    }
+}
+
+pub struct HiResMouseConfig<'a> {
+    interface: InterfaceConfig<'a, InBytes8, OutBytes8, ReportSingle>,
+}
+
+impl<'a> Default for HiResMouseConfig<'a> {
+    fn default() -> Self {
+        Self {
+            interface: InterfaceBuilder::new(HIRES_MOUSE_REPORT_DESCRIPTOR)
+                .unwrap()
+                .boot_device(InterfaceProtocol::Mouse)
+                .description("Hi-res Wheel Mouse")
+                .in_endpoint(10.millis())
+                .unwrap()
+                .without_out_endpoint()
+                .build(),
+        }
+    }
+}
+
+impl<'a, B: UsbBus + 'a> UsbAllocatable<'a, B> for HiResMouseConfig<'a> {
+    type Allocated = HiResMouse<'a, B>;
+
+    fn allocate(self, usb_alloc: &'a UsbBusAllocator<B>) -> Self::Allocated {
+        HiResMouse { interface: Interface::new(usb_alloc, self.interface), multiplier: 1 }
+    }
+}
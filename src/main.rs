@@ -3,11 +3,20 @@
 
 use panic_halt as _;
 use stm32f4xx_hal as hal;
-mod layout;
 mod oled;
-mod mouse;
-
-#[rtic::app(device = hal::pac, dispatchers = [TIM1_CC])]
+mod flash;
+mod abs_mouse;
+mod system_control;
+#[cfg(feature = "hires-scroll")]
+mod hires_mouse;
+#[cfg(feature = "cirque")]
+mod cirque;
+
+// both are on I2C2
+#[cfg(all(feature = "oled", feature = "cirque"))]
+compile_error!("the cirque trackpad takes the OLED bus");
+
+#[rtic::app(device = hal::pac, dispatchers = [TIM1_CC, TIM1_UP_TIM10])]
 mod app {
     use {
         super::*,
@@ -20,7 +29,8 @@ mod app {
             prelude::*,
             serial
         },
-        cortex_m::interrupt,
+        cortex_m::{interrupt, peripheral::SCB},
+        core::{mem::MaybeUninit, ptr::{addr_of, addr_of_mut, read_volatile, write_volatile}},
 
         nb::block,
 
@@ -28,15 +38,46 @@ mod app {
 
         keyberon::{
             debounce::Debouncer,
-            key_code::KbHidReport,
-            layout::{Event, Layout, CustomEvent},
+            layout::{Event, CustomEvent},
             matrix::DirectPinMatrix,
-            keyboard
         },
 
-        layout::{LAYERS, CustomAction},
+        usbd_human_interface_device::{
+            device::{
+                consumer::{ConsumerControl, ConsumerControlConfig},
+                keyboard::{NKROBootKeyboard, NKROBootKeyboardConfig},
+            },
+            page,
+            prelude::*,
+            UsbHidError,
+        },
+        frunk::HList,
+
+        yuki_core::{
+            keyboard::{Keyboard, NUM_LAYERS},
+            holdtap::{HoldTaps, HTAction},
+            layout::CustomAction,
+            link::{ser, de, take_motion, Msg},
+            mouse::MAction,
+            reports::{Report, Reports},
+            restart::Restart,
+            settings::Settings,
+        },
         oled::OLED,
-        mouse::Mouse,
+        flash::SettingsFlash,
+        abs_mouse::{AbsMouse, AbsMouseConfig},
+        system_control::{SystemControl, SystemControlConfig},
+    };
+
+    #[cfg(not(feature = "hires-scroll"))]
+    use usbd_human_interface_device::device::mouse::{WheelMouse, WheelMouseConfig};
+    #[cfg(feature = "hires-scroll")]
+    use hires_mouse::{HiResMouse, HiResMouseConfig};
+    #[cfg(feature = "cirque")]
+    use {
+        hal::{i2c::{I2c, Mode}, pac::I2C2},
+        cirque::Cirque,
+        yuki_core::pointing::Sensor,
     };
 
 
@@ -44,31 +85,91 @@ mod app {
     const PRODUCT: &str = "YUKI";
     const SERIAL_NUMBER: &str = "1337";
 
+    // of the matrix and the keyboard tick, in Hz
+    const SCAN_RATE: u32 = 1000;
+    const SUSPENDED_SCAN_RATE: u32 = 100;
+
     const NUM_COLS: usize = 6;
     const NUM_ROWS: usize = 4;
 
-    const NUM_LAYERS: usize = LAYERS.len();
+    #[cfg(not(feature = "hires-scroll"))]
+    type MouseDevice = WheelMouse<'static, UsbBusType>;
+    #[cfg(feature = "hires-scroll")]
+    type MouseDevice = HiResMouse<'static, UsbBusType>;
+    // an NKRO bitmap, or the boot report when the host asks for the boot protocol
+    type KeyboardDevice = NKROBootKeyboard<'static, UsbBusType>;
+    // all the interfaces in one class, polled together
+    type HidClass = UsbHidClass<'static, UsbBusType, HList!(
+        KeyboardDevice,
+        MouseDevice,
+        AbsMouse<'static, UsbBusType>,
+        ConsumerControl<'static, UsbBusType>,
+        SystemControl<'static, UsbBusType>,
+    )>;
+
+    /// False while the endpoint of the report is busy.
+    fn write_report(h: &mut HidClass, report: &Report) -> bool {
+        let result = match report {
+            Report::Keyboard(keys) => h.device::<KeyboardDevice, _>()
+                .write_report(keys.iter().map(|k| page::Keyboard::from(*k as u8))),
+            Report::Mouse(r) => h.device::<MouseDevice, _>().write_report(r),
+            Report::Abs(r) => h.device::<AbsMouse<'static, UsbBusType>, _>().write_report(r),
+            Report::Consumer(r) => h.device::<ConsumerControl<'static, UsbBusType>, _>().write_report(r),
+            Report::System(c) => h.device::<SystemControl<'static, UsbBusType>, _>().write_report(*c),
+        };
+
+        // a keyboard report equal to the last one is a `Duplicate`, that
+        // and the other errors drop it so the queue keeps moving
+        !matches!(result, Err(UsbHidError::WouldBlock))
+    }
 
-    const USE_RIGHT_USB_INIT: bool = true;
+    /// Sets or clears the resume signalling, the host wants it for 1 to
+    /// 15 ms.
+    fn remote_wakeup(on: bool) {
+        unsafe { (*hal::pac::OTG_FS_DEVICE::ptr()).dctl.modify(|_, w| w.rwusig().bit(on)) }
+    }
 
+    // not zeroed at boot, so it tells init that the reset was for the bootloader
+    #[link_section = ".uninit.DFU_FLAG"]
+    static mut DFU_FLAG: MaybeUninit<u32> = MaybeUninit::uninit();
+    const DFU_MAGIC: u32 = 0xDF00_B007;
+
+    /// Detaches from the host and resets, with `dfu` idle jumps to the
+    /// bootloader after the next init.
+    fn restart(dfu: bool) -> ! {
+        unsafe {
+            if dfu {
+                write_volatile(addr_of_mut!(DFU_FLAG).cast(), DFU_MAGIC);
+            }
+            // soft disconnect so the host sees the keyboard leave
+            (*hal::pac::OTG_FS_DEVICE::ptr()).dctl.modify(|_, w| w.sdis().set_bit());
+        }
 
-    pub struct Leds { caps_lock:  PC13<Output<PushPull>> }
+        // ~10ms at 84MHz for the host to notice
+        cortex_m::asm::delay(840_000);
+        SCB::sys_reset()
+    }
 
-    impl keyboard::Leds for Leds {
-        fn caps_lock(&mut self, status: bool) {
-            match status {
-                true => self.caps_lock.set_low(),
-                false => self.caps_lock.set_high(),
-            }
+    fn take_dfu_flag() -> bool {
+        unsafe {
+            let set = read_volatile(addr_of!(DFU_FLAG).cast::<u32>()) == DFU_MAGIC;
+            write_volatile(addr_of_mut!(DFU_FLAG).cast(), 0);
+            set
         }
     }
 
     #[shared]
     struct Shared {
         usb_dev: UsbDevice<'static, UsbBusType>,
-        usb_class: keyberon::Class<'static, UsbBusType, Leds>,
-        layout: Layout<{NUM_COLS*2}, NUM_ROWS, NUM_LAYERS, CustomAction>,
-        mouse: Mouse
+        hid_class: HidClass,
+        keyboard: Keyboard,
+        settings: Settings,
+        flash: SettingsFlash,
+        tx: serial::Tx<USART1>,
+        // counts not sent to the other half yet, the USART interrupt sends
+        // them so the tick doesn't wait on the link
+        #[cfg(feature = "cirque")]
+        pending_motion: (i16, i16),
     }
 
     #[local]
@@ -76,12 +177,20 @@ mod app {
         matrix: DirectPinMatrix<EPin<Input>, NUM_COLS, NUM_ROWS>,
         debouncer: Debouncer<[[bool; NUM_COLS]; NUM_ROWS]>,
         timer: CounterHz<TIM2>,
-        tx: serial::Tx<USART1>,
         rx: serial::Rx<USART1>,
         enter_dfu: bool,
-        use_right_usb: bool,
+        synced: bool,
+        // what the tick last set the scan rate and OLED for
+        suspended: bool,
+        // ticks left of the resume signalling
+        wakeup_ticks: u8,
+        caps_lock: PC13<Output<PushPull>>,
+        reports: Reports,
         #[cfg(feature = "oled")]
         oled: OLED,
+        // None when the trackpad didn't answer, the keys still work
+        #[cfg(feature = "cirque")]
+        sensor: Option<Cirque<I2c<I2C2>>>,
     }
 
 
@@ -92,6 +201,9 @@ mod app {
         ]
     )]
     fn init(ctx: init::Context) -> (Shared, Local, init::Monotonics) {
+        let flash = SettingsFlash::new(ctx.device.FLASH);
+        let settings = Settings::load(&flash);
+
         let mut clocks = ctx
             .device
             .RCC
@@ -111,7 +223,7 @@ mod app {
         let pb12 = gpiob.pb12.into_pull_up_input();
 
         let mut timer = ctx.device.TIM2.counter_hz(&mut clocks);
-        timer.start(1.kHz()).unwrap();
+        timer.start(SCAN_RATE.Hz()).unwrap();
         timer.listen(hal::timer::Event::Update);
 
 
@@ -131,9 +243,17 @@ mod app {
         *ctx.local.bus = Some(UsbBus::new(usb, ctx.local.ep_memory));
         let usb_bus = ctx.local.bus.as_ref().unwrap();
 
-        let usb_class = keyberon::new_class(usb_bus, Leds {caps_lock});
-
-        let mouse = Mouse::new(usb_bus);
+        #[cfg(not(feature = "hires-scroll"))]
+        let mouse_config = WheelMouseConfig::default();
+        #[cfg(feature = "hires-scroll")]
+        let mouse_config = HiResMouseConfig::default();
+        let hid_class = UsbHidClassBuilder::new()
+            .add_device(NKROBootKeyboardConfig::default())
+            .add_device(mouse_config)
+            .add_device(AbsMouseConfig::default())
+            .add_device(ConsumerControlConfig::default())
+            .add_device(SystemControlConfig::default())
+            .build(usb_bus);
 
         let usb_dev = UsbDeviceBuilder::new(usb_bus, UsbVidPid(0x16c0, 0x27db))
         .strings(&[StringDescriptors::default()
@@ -141,11 +261,12 @@ mod app {
             .product(PRODUCT)
             .serial_number(SERIAL_NUMBER)])
         .unwrap()
+        .supports_remote_wakeup(true)
         .build();
 
         // easier dfu check if keys are pressed
         // do this a bit after setting the pins to high
-        let enter_dfu = pa0.is_low() && pb12.is_low();
+        let enter_dfu = (pa0.is_low() && pb12.is_low()) || take_dfu_flag();
 
         let matrix = interrupt::free(move |_| {
             DirectPinMatrix::new([
@@ -196,12 +317,21 @@ mod app {
         serial.listen(serial::Event::RxNotEmpty);
         let (tx, rx) = serial.split();
 
+        let mut keyboard = Keyboard::new();
+        keyboard.set_default_layer(settings.default_layer);
+        keyboard.hold_taps = HoldTaps::new(settings.hold_tap_terms);
+        keyboard.mouse.toggled_speed = settings.mouse_speed;
+
         (
             Shared {
                 usb_dev,
-                usb_class,
-                layout: Layout::new(&LAYERS),
-                mouse,
+                hid_class,
+                keyboard,
+                settings,
+                flash,
+                tx,
+                #[cfg(feature = "cirque")]
+                pending_motion: (0, 0),
             },
             Local {
                 matrix,
@@ -211,51 +341,191 @@ mod app {
                     [[false; NUM_COLS]; NUM_ROWS],
                     NUM_LAYERS.try_into().unwrap()
                 ),
-                tx, rx,
+                rx,
                 enter_dfu,
-                use_right_usb: USE_RIGHT_USB_INIT,
+                synced: false,
+                suspended: false,
+                wakeup_ticks: 0,
+                caps_lock,
+                reports: Reports::new(),
                 #[cfg(feature = "oled")]
                 oled: OLED::new(
                     gpiob.pb10.into_alternate().set_open_drain(),
                     gpiob.pb3.into_alternate().set_open_drain(),
                     ctx.device.I2C2, &clocks
-                )
+                ),
+                #[cfg(feature = "cirque")]
+                sensor: Cirque::new(I2c::new(
+                    ctx.device.I2C2,
+                    (
+                        gpiob.pb10.into_alternate().set_open_drain(),
+                        gpiob.pb3.into_alternate().set_open_drain(),
+                    ),
+                    Mode::fast(400.kHz(), hal::i2c::DutyCycle::Ratio2to1),
+                    &clocks
+                )).ok(),
             },
             init::Monotonics(),
        )
     }
 
-    #[task(priority = 3, capacity = 8, shared = [layout])]
+    #[task(priority = 3, capacity = 8, shared = [keyboard])]
     fn handle_event(mut ctx: handle_event::Context, event: Event) {
-        ctx.shared.layout.lock(|l| l.event(event))
+        ctx.shared.keyboard.lock(|k| k.event(event))
+    }
+
+    #[task(priority = 3, capacity = 8, shared = [keyboard])]
+    fn motion(mut ctx: motion::Context, motion: (i16, i16)) {
+        ctx.shared.keyboard.lock(|k| k.motion(motion))
     }
 
-    #[task(binds = USART1, priority = 2, local = [rx])]
-    fn rx(ctx: rx::Context) {
-        if let Ok(b) = ctx.local.rx.read() {
-            if let Ok(event) = de(b) {
+    #[task(priority = 3, capacity = 2, shared = [keyboard])]
+    fn set_suspended(mut ctx: set_suspended::Context, suspended: bool) {
+        ctx.shared.keyboard.lock(|k| { k.suspend.set(suspended); })
+    }
+
+    // the other half only sends it once its bootloader key was held long
+    // enough, this half holds it too, so a noise byte is not enough
+    #[task(priority = 3, capacity = 1, shared = [keyboard])]
+    fn bootloader(mut ctx: bootloader::Context) {
+        if ctx.shared.keyboard.lock(|k| k.confirm.pending()) == Some(Restart::Bootloader) {
+            restart(true)
+        }
+    }
+
+    #[task(binds = USART1, priority = 2, local = [rx], shared = [tx, pending_motion])]
+    fn link(ctx: link::Context) {
+        // a byte out per TXE interrupt, each counts on this half as it goes
+        #[cfg(feature = "cirque")]
+        (ctx.shared.tx, ctx.shared.pending_motion).lock(|tx, pending| {
+            if !tx.is_tx_empty() {
+                return;
+            }
+
+            let x = take_motion(&mut pending.0);
+            let y = if x == 0 { take_motion(&mut pending.1) } else { 0 };
+            let msg = match (x, y) {
+                (0, 0) => return tx.unlisten(),
+                (0, y) => Msg::MotionY(y),
+                (x, _) => Msg::MotionX(x),
+            };
+            let _ = tx.write(ser(msg));
+            let _ = motion::spawn((x as i16, y as i16));
+        });
+
+        match ctx.local.rx.read().map(de) {
+            Ok(Ok(Msg::Event(event))) => {
                 #[cfg(not(feature = "right"))]
                 let event = event.transform(|i, j| (i, 11 - j));
 
                 handle_event::spawn(event).unwrap();
+            },
+            Ok(Ok(Msg::DefaultLayer(layer))) => { let _ = set_default_layer::spawn(layer); },
+            Ok(Ok(Msg::Bootloader)) => { let _ = bootloader::spawn(); },
+            Ok(Ok(Msg::Suspend(suspended))) => { let _ = set_suspended::spawn(suspended); },
+            Ok(Ok(Msg::MotionX(d))) => { let _ = motion::spawn((d as i16, 0)); },
+            Ok(Ok(Msg::MotionY(d))) => { let _ = motion::spawn((0, d as i16)); },
+            _ => ()
+        }
+    }
+
+    #[task(priority = 1, capacity = 2, shared = [keyboard, settings, flash])]
+    fn set_default_layer(mut ctx: set_default_layer::Context, layer: u8) {
+        if !ctx.shared.keyboard.lock(|k| k.set_default_layer(layer)) {
+            return;
+        }
+
+        let erased = (ctx.shared.settings, ctx.shared.flash).lock(|s, flash| {
+            s.default_layer != layer && {
+                s.default_layer = layer;
+                s.store(flash)
             }
+        });
+
+        if erased {
+            let _ = release_other_half::spawn();
         }
     }
 
+    // both halves see the key presses, so each saves its own terms
+    #[task(priority = 1, shared = [keyboard, settings, flash])]
+    fn save_hold_taps(mut ctx: save_hold_taps::Context) {
+        let terms = ctx.shared.keyboard.lock(|k| k.hold_taps.terms);
+
+        let erased = (ctx.shared.settings, ctx.shared.flash).lock(|s, flash| {
+            s.hold_tap_terms != terms && {
+                s.hold_tap_terms = terms;
+                s.store(flash)
+            }
+        });
+
+        if erased {
+            let _ = release_other_half::spawn();
+        }
+    }
+
+    // the same for the toggled mouse speed
+    #[task(priority = 1, shared = [keyboard, settings, flash])]
+    fn save_mouse_speed(mut ctx: save_mouse_speed::Context) {
+        let speed = ctx.shared.keyboard.lock(|k| k.mouse.toggled_speed);
+
+        let erased = (ctx.shared.settings, ctx.shared.flash).lock(|s, flash| {
+            s.mouse_speed != speed && {
+                s.mouse_speed = speed;
+                s.store(flash)
+            }
+        });
+
+        if erased {
+            let _ = release_other_half::spawn();
+        }
+    }
+
+    // the cpu stalls during an erase and the link bytes of the other half
+    // are lost meanwhile, its keys are released instead of getting stuck
+    #[task(priority = 3, shared = [keyboard])]
+    fn release_other_half(mut ctx: release_other_half::Context) {
+        #[cfg(feature = "right")] let cols = 0..6;
+        #[cfg(not(feature = "right"))] let cols = 6..12;
+
+        ctx.shared.keyboard.lock(|k| k.release_cols(cols))
+    }
+
     #[task(
         binds=TIM2,
         priority=1,
-        local=[debouncer, matrix, timer, tx, oled, use_right_usb],
-        shared=[usb_dev, usb_class, layout, mouse]
+        local=[debouncer, matrix, timer, oled, synced, suspended, wakeup_ticks, reports, sensor],
+        shared=[usb_dev, hid_class, keyboard, settings, tx, pending_motion]
     )]
     fn tick(mut ctx: tick::Context) {
         ctx.local.timer.wait().ok();
 
+        // while the host sleeps the matrix is scanned slower and the OLED is off
+        let suspended = ctx.shared.keyboard.lock(|k| k.suspend.is_suspended());
+        if suspended != *ctx.local.suspended {
+            *ctx.local.suspended = suspended;
+            let rate = if suspended { SUSPENDED_SCAN_RATE } else { SCAN_RATE };
+            ctx.local.timer.start(rate.Hz()).unwrap();
+
+            #[cfg(feature = "oled")]
+            ctx.local.oled.set_on(!suspended);
+        }
+
+        if *ctx.local.wakeup_ticks > 0 {
+            *ctx.local.wakeup_ticks -= 1;
+            if *ctx.local.wakeup_ticks == 0 {
+                remote_wakeup(false);
+            }
+        }
+
+        // the keyboard repeats its report at the idle rate the host asked for
+        let _ = ctx.shared.hid_class.lock(|h| h.tick());
+
         ctx.local
             .debouncer
             .events(ctx.local.matrix.get().unwrap())
             .for_each(|e| {
-                block!(ctx.local.tx.write(ser(e))).unwrap();
+                ctx.shared.tx.lock(|tx| block!(tx.write(ser(Msg::Event(e))))).unwrap();
 
                 #[cfg(feature = "right")]
                 let e = e.transform(|i, j| (i, 11 - j));
@@ -263,41 +533,106 @@ mod app {
                 handle_event::spawn(e).unwrap();
             });
 
-        ctx.shared.mouse.lock(|m| m.mouse_tick());
-        match ctx.shared.layout.lock(|l| l.tick()) {
-            CustomEvent::NoEvent => (),
-            CustomEvent::Press(CustomAction::USB) => *ctx.local.use_right_usb = !*ctx.local.use_right_usb,
-            CustomEvent::Press(CustomAction::M(maction)) => ctx.shared.mouse.lock(|m| m.handle_mouse_btn(maction, true)),
-            CustomEvent::Release(CustomAction::M(maction)) => ctx.shared.mouse.lock(|m| m.handle_mouse_btn(maction, false)),
+        // the link carries a few counts per message, the USART interrupt sends
+        // them as it is free, only the key events go over it while suspended
+        #[cfg(feature = "cirque")]
+        if !suspended {
+            if let Some(Ok((x, y))) = ctx.local.sensor.as_mut().map(|s| s.motion()) {
+                if (x, y) != (0, 0) {
+                    (&mut ctx.shared.tx, &mut ctx.shared.pending_motion).lock(|tx, pending| {
+                        *pending = (pending.0.saturating_add(x), pending.1.saturating_add(y));
+                        tx.listen();
+                    });
+                }
+            }
+        }
+
+        // the rest of the custom actions are handled by the keyboard itself
+        match ctx.shared.keyboard.lock(|k| k.tick()) {
+            CustomEvent::Press(CustomAction::DefaultLayer(layer)) => {
+                ctx.shared.tx.lock(|tx| block!(tx.write(ser(Msg::DefaultLayer(*layer))))).unwrap();
+                let _ = set_default_layer::spawn(*layer);
+            },
+            CustomEvent::Press(CustomAction::HT(HTAction::Increase | HTAction::Decrease)) => {
+                let _ = save_hold_taps::spawn();
+            },
+            CustomEvent::Press(CustomAction::M(MAction::ToggleSpeed(_))) => {
+                let _ = save_mouse_speed::spawn();
+            },
             _ => ()
         }
 
-        let use_right_usb = ctx.local.use_right_usb;
+        // both halves see the held key, the message covers the one that
+        // did not (e.g. it got the release a tick later)
+        match ctx.shared.keyboard.lock(|k| k.restart.take()) {
+            Some(Restart::Bootloader) => {
+                ctx.shared.tx.lock(|tx| {
+                    block!(tx.write(ser(Msg::Bootloader))).unwrap();
+                    block!(tx.flush()).unwrap();
+                });
+                restart(true)
+            },
+            Some(Restart::Reset) => restart(false),
+            None => (),
+        }
 
-        if ctx.shared.usb_dev.lock(|d| d.state()) == UsbDeviceState::Configured {
-            #[cfg(feature = "right")] let cond = *use_right_usb;
-            #[cfg(not(feature = "right"))] let cond = !*use_right_usb;
+        let use_right_usb = ctx.shared.keyboard.lock(|k| k.use_right_usb);
+        #[cfg(feature = "right")] let cond = use_right_usb;
+        #[cfg(not(feature = "right"))] let cond = !use_right_usb;
 
-            if cond {
-                let report: KbHidReport = ctx.shared.layout.lock(|l| l.keycodes().collect());
-                if ctx.shared.usb_class.lock(|k| k.device_mut().set_keyboard_report(report.clone())) {
-                    while let Ok(0) = ctx.shared.usb_class.lock(|k| k.write(report.as_bytes())) {}
-                }
+        let (state, wakeup_allowed) = ctx.shared.usb_dev.lock(|d| (d.state(), d.remote_wakeup_enabled()));
+        let wake = ctx.shared.keyboard.lock(|k| k.suspend.take_wake());
+
+        // the half on USB tells the other one when the host sleeps or wakes
+        if cond {
+            let suspended = state == UsbDeviceState::Suspend;
+            if ctx.shared.keyboard.lock(|k| k.suspend.set(suspended)) {
+                ctx.shared.tx.lock(|tx| block!(tx.write(ser(Msg::Suspend(suspended))))).unwrap();
+            }
+
+            if wake && wakeup_allowed && *ctx.local.wakeup_ticks == 0 {
+                remote_wakeup(true);
+                // ticks are 10 ms apart while suspended
+                *ctx.local.wakeup_ticks = 1;
+            }
+        }
+
+        let reports = ctx.local.reports;
+        if state == UsbDeviceState::Configured && cond {
+            // the half talking to the host decides the default layer after boot
+            if !*ctx.local.synced {
+                let layer = ctx.shared.settings.lock(|s| s.default_layer);
+                ctx.shared.tx.lock(|tx| block!(tx.write(ser(Msg::DefaultLayer(layer))))).unwrap();
+                *ctx.local.synced = true;
+            }
 
-                while let Ok(()) = ctx.shared.mouse.lock(|m| m.mouse.device().write_report(&m.report)) {}
+            #[cfg(feature = "hires-scroll")]
+            {
+                let multiplier = ctx.shared.hid_class.lock(|h| h.device::<MouseDevice, _>().multiplier());
+                ctx.shared.keyboard.lock(|k| k.mouse.wheel_multiplier = multiplier);
             }
+
+            // only the reports of changes, each endpoint takes one per
+            // poll so the rest wait for the next ticks
+            ctx.shared.keyboard.lock(|k| reports.update(k));
+            ctx.shared.hid_class.lock(|h| reports.send(|r| write_report(h, r)));
+        } else {
+            // the other half or no host yet, the motion would jump the
+            // cursor once this half sends
+            ctx.shared.keyboard.lock(|k| reports.skip(k));
         }
 
         #[cfg(feature = "oled")]
-        ctx.local.oled.draw(
-            ctx.shared.layout.lock(|l| l.current_layer()),
-            *use_right_usb
-        )
+        if !suspended {
+            let (layer, host_os, info) = ctx.shared.keyboard.lock(|k| (k.layout.current_layer(), k.os.host(), k.info()));
+            ctx.local.oled.draw(layer, use_right_usb, host_os, info.as_deref())
+        }
     }
 
     #[idle(local = [enter_dfu])]
     fn idle(ctx: idle::Context) -> ! {
-        // if pa0 && pb12 are shorted and the board resets this will load dfu
+        // if pa0 && pb12 are shorted and the board resets, or the bootloader
+        // key was held, this will load dfu
         if *ctx.local.enter_dfu {
             unsafe { cortex_m::asm::bootload(0x1FFF0000 as _) }
         }
@@ -305,35 +640,18 @@ mod app {
         loop { rtic::export::wfi() }
     }
 
-    // im using one byte for the coords and the is_press status
-    // the last three bits are the y
-    // the second to last three are the x
-    // the first bit is the is_press status
-    //                                     press     5       2
-    // so for example Event::Press(2, 5) == (1) 0 (1 0 1) (0 1 0)
-    //
-    // this only works with 8 or less columns or rows
-    fn ser(e: Event) -> u8 {
-        let (y, x) = e.coord();
-        y | x << 3 | (e.is_press() as u8) << 7
-    }
-    fn de(n: u8) -> Result<Event, ()> {
-        match n & 128 {
-            128 => Ok(Event::Press(n & 7, (n & 56) >> 3)),
-            0 => Ok(Event::Release(n & 7, (n & 56) >> 3)),
-            _ => Err(())
-        } 
-    }
-
-    use usb_device::class::UsbClass;
-    #[task(binds = OTG_FS, priority = 3, shared = [usb_dev, usb_class, mouse])]
+    #[task(binds = OTG_FS, priority = 3, local = [caps_lock], shared = [usb_dev, hid_class, keyboard])]
     fn usb(ctx: usb::Context) {
-        (ctx.shared.usb_dev, ctx.shared.usb_class, ctx.shared.mouse)
-        .lock(|usb_dev, kb, m| {
-            if m.active {
-                if usb_dev.poll(&mut [&mut m.mouse, kb]) { kb.poll() }
-            } else {
-                if usb_dev.poll(&mut [kb]) { kb.poll() }
+        let caps_lock = ctx.local.caps_lock;
+        (ctx.shared.usb_dev, ctx.shared.hid_class, ctx.shared.keyboard)
+        .lock(|usb_dev, hid, keyboard| {
+            if !usb_dev.poll(&mut [&mut keyboard.os, hid]) {
+                return;
+            }
+
+            // the caps lock led is on when low
+            if let Ok(leds) = hid.device::<KeyboardDevice, _>().read_report() {
+                caps_lock.set_state((!leds.caps_lock).into());
             }
         });
     }
@@ -1,36 +1,23 @@
 use stm32f4xx_hal as hal;
 
 use {
-    embedded_graphics::{
-        mono_font::{iso_8859_16::FONT_10X20 as FONT, MonoTextStyleBuilder, MonoTextStyle},
-        pixelcolor::BinaryColor,
-        prelude::*,
-        image::{Image, ImageRaw},
-        text::{Baseline, Text},
-    },
     hal::{
         prelude::*,
         i2c::{Mode, I2c},
         pac::I2C2,
         gpio
     },
-    ufmt::uwrite,
+    yuki_core::{oled::Screen, os::HostOs},
 
     ssd1306::{mode::BufferedGraphicsMode, prelude::*, Ssd1306}
 };
 
 pub type Display = Ssd1306<I2CInterface<I2c<I2C2>>, DisplaySize128x32, BufferedGraphicsMode<DisplaySize128x32>>;
 
-const TEXT_STYLE: MonoTextStyle<BinaryColor> = MonoTextStyleBuilder::new()
-    .font(&FONT)
-    .text_color(BinaryColor::On)
-    .build();
-
 #[allow(dead_code)]
 pub struct OLED {
     display: Display,
-    prev_layer: usize,
-    prev_uru: bool,
+    screen: Screen,
 }
 
 #[allow(dead_code)]
@@ -60,53 +47,18 @@ impl OLED {
 
         Self {
             display,
-            prev_layer: 0,
-            prev_uru: false,
+            screen: Screen::new(),
         }
     }
 
-    pub fn draw(&mut self, curr_layer: usize, uru: bool) {
-        if 
-            curr_layer != self.prev_layer ||
-            uru != self.prev_uru
-        {
-            self.prev_layer = curr_layer;
-            self.prev_uru = uru;
-
-            let display = &mut self.display;
-            let mut txt: heapless::String<32> = heapless::String::new();
-
-
-            {
-                let img: ImageRaw<BinaryColor> = ImageRaw::new(include_bytes!("../img.raw"), 32);
-                Image::new(&img, Point::new(0, 0)).draw(display).unwrap();
-            }
-
-            {
-                let _ = uwrite!(&mut txt, "L {}", curr_layer);
-
-                Text::with_baseline(
-                    &txt,
-                    Point::new(2, 55),
-                    TEXT_STYLE,
-                    Baseline::Top
-                ).draw(display).unwrap();
-            }
-
-            {
-                txt.clear(); 
-                let _ = uwrite!(&mut txt, "{}", 
-                    if uru { "-->" } else { "<--" }
-                );
-
-                Text::with_baseline(&txt,
-                    Point::new(2, 100),
-                    TEXT_STYLE,
-                    Baseline::Top
-                ).draw(display).unwrap();
-            }
+    /// Blanks the display, what it showed is kept for when it is back on.
+    pub fn set_on(&mut self, on: bool) {
+        self.display.set_display_on(on).unwrap();
+    }
 
-            display.flush().unwrap();
+    pub fn draw(&mut self, curr_layer: usize, uru: bool, os: HostOs, info: Option<&str>) {
+        if self.screen.draw(&mut self.display, curr_layer, uru, os, info).unwrap() {
+            self.display.flush().unwrap();
         }
     }
 }
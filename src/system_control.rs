@@ -0,0 +1,86 @@
+use {
+    usb_device::bus::{UsbBus, UsbBusAllocator},
+    usbd_human_interface_device::{
+        device::DeviceClass,
+        interface::{
+            InBytes8, Interface, InterfaceBuilder, InterfaceConfig, OutNone, ReportSingle,
+            UsbAllocatable,
+        },
+        UsbHidError,
+    },
+    stm32f4xx_hal::prelude::*,
+    yuki_core::hid::SystemControl as Control,
+};
+
+// one of power down, sleep or wake up, 0 for none
+#[rustfmt::skip]
+const SYSTEM_CONTROL_REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01,       // Usage Page (Generic Desktop)
+    0x09, 0x80,       // Usage (System Control)
+    0xA1, 0x01,       // Collection (Application)
+    0x19, 0x81,       //   Usage Minimum (System Power Down)
+    0x29, 0x83,       //   Usage Maximum (System Wake Up)
+    0x15, 0x01,       //   Logical Minimum (1)
+    0x25, 0x03,       //   Logical Maximum (3)
+    0x75, 0x08,       //   Report Size (8)
+    0x95, 0x01,       //   Report Count (1)
+    0x81, 0x00,       //   Input (Data, Array, Absolute)
+    0xC0,             // End Collection
+];
+
+/// Power, sleep and wake keys, next to the consumer device.
+pub struct SystemControl<'a, B: UsbBus> {
+    interface: Interface<'a, B, InBytes8, OutNone, ReportSingle>,
+}
+
+impl<'a, B: UsbBus> SystemControl<'a, B> {
+    pub fn write_report(&mut self, control: Option<Control>) -> Result<(), UsbHidError> {
+        // the array starts at the power down usage
+        let index = control.map_or(0, |c| c as u8 - 0x80);
+
+        self.interface
+            .write_report(&[index])
+            .map(|_| ())
+            .map_err(UsbHidError::from)
+    }
+}
+
+impl<'a, B: UsbBus> DeviceClass<'a> for SystemControl<'a, B> {
+    type I = Interface<'a, B, InBytes8, OutNone, ReportSingle>;
+
+    fn interface(&mut self) -> &mut Self::I {
+        &mut self.interface
+    }
+
+    fn reset(&mut self) {}
+
+    fn tick(&mut self) -> Result<(), UsbHidError> {
+        Ok(())
+    }
+}
+
+pub struct SystemControlConfig<'a> {
+    interface: InterfaceConfig<'a, InBytes8, OutNone, ReportSingle>,
+}
+
+impl<'a> Default for SystemControlConfig<'a> {
+    fn default() -> Self {
+        Self {
+            interface: InterfaceBuilder::new(SYSTEM_CONTROL_REPORT_DESCRIPTOR)
+                .unwrap()
+                .description("System Control")
+                .in_endpoint(10.millis())
+                .unwrap()
+                .without_out_endpoint()
+                .build(),
+        }
+    }
+}
+
+impl<'a, B: UsbBus + 'a> UsbAllocatable<'a, B> for SystemControlConfig<'a> {
+    type Allocated = SystemControl<'a, B>;
+
+    fn allocate(self, usb_alloc: &'a UsbBusAllocator<B>) -> Self::Allocated {
+        SystemControl { interface: Interface::new(usb_alloc, self.interface) }
+    }
+}
@@ -0,0 +1,59 @@
+//! Draws every layer of yuki-core/src/layout.rs as an SVG and as
+//! keyboard-layout-editor JSON.
+//!
+//! usage: keymap-viz [layout.rs] [output dir] [kicad_pcb]
+
+use {
+    std::{env, fs, path::PathBuf, process::exit},
+    yuki_tools::{geometry, keymap::Keymap, kle, layer_keys, pcb, svg},
+};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let layout = PathBuf::from(args.next().unwrap_or("yuki-core/src/layout.rs".into()));
+    let out = PathBuf::from(args.next().unwrap_or("keymap".into()));
+    let board = PathBuf::from(args.next().unwrap_or("yuki_pcb/yuki_pcb.kicad_pcb".into()));
+
+    let keymap = fs::read_to_string(&layout)
+        .map_err(|e| e.to_string())
+        .and_then(|src| Keymap::parse(&src))
+        .unwrap_or_else(|e| {
+            eprintln!("{}: {}", layout.display(), e);
+            exit(1)
+        });
+
+    let half = fs::read_to_string(&board)
+        .map_err(|e| e.to_string())
+        .and_then(|src| pcb::matrix(&pcb::switches(&src)?))
+        .unwrap_or_else(|e| {
+            eprintln!("{}: {}", board.display(), e);
+            exit(1)
+        });
+
+    let keys = geometry::keyboard(&half);
+
+    if let Err(e) = fs::create_dir_all(&out) {
+        eprintln!("{}: {}", out.display(), e);
+        exit(1)
+    }
+
+    for (i, layer) in keymap.layers.iter().enumerate() {
+        let name = match layer.name.is_empty() {
+            true => format!("Layer {}", i),
+            false => format!("Layer {} - {}", i, layer.name),
+        };
+        let layer_keys = layer_keys(layer, &keymap, &keys);
+
+        for (ext, data) in [
+            ("svg", svg::render(&name, &layer_keys)),
+            ("json", kle::render(&name, &layer_keys)),
+        ] {
+            let path = out.join(format!("layer{}.{}", i, ext));
+            if let Err(e) = fs::write(&path, data) {
+                eprintln!("{}: {}", path.display(), e);
+                exit(1)
+            }
+            println!("{}", path.display());
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Prints the physical position of every key of the keyboard, as placed
+//! on the pcb, by its `layout!` (row, col). With `--kle` the output is
+//! keyboard-layout-editor JSON with `row,col` legends, as used by VIA
+//! and Vial keyboard definitions.
+//!
+//! usage: pcb-layout [--kle] [kicad_pcb]
+
+use {
+    std::{env, fs, process::exit},
+    yuki_tools::{geometry, kle, labels::Label, pcb},
+};
+
+fn main() {
+    let (flags, paths): (Vec<String>, Vec<String>) = env::args().skip(1).partition(|a| a.starts_with("--"));
+    let board = paths.first().map(String::as_str).unwrap_or("yuki_pcb/yuki_pcb.kicad_pcb");
+
+    let half = fs::read_to_string(board)
+        .map_err(|e| e.to_string())
+        .and_then(|src| pcb::matrix(&pcb::switches(&src)?))
+        .unwrap_or_else(|e| {
+            eprintln!("{}: {}", board, e);
+            exit(1)
+        });
+
+    let mut keys = geometry::keyboard(&half);
+    keys.sort_by_key(|k| (k.row, k.col));
+
+    if flags.iter().any(|f| f == "--kle") {
+        let keys: Vec<_> = keys.into_iter()
+            .map(|k| (k, Label { tap: format!("{},{}", k.row, k.col), ..Default::default() }))
+            .collect();
+
+        print!("{}", kle::render("YUKI", &keys));
+        return;
+    }
+
+    println!("[");
+    for (i, k) in keys.iter().enumerate() {
+        println!(
+            "  {{\"row\": {}, \"col\": {}, \"x\": {:.3}, \"y\": {:.3}, \"angle\": {}}}{}",
+            k.row, k.col, k.x, k.y, k.angle,
+            if i + 1 < keys.len() { "," } else { "" }
+        );
+    }
+    println!("]");
+}
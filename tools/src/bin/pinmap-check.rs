@@ -0,0 +1,42 @@
+//! Checks that the pins in src/main.rs are the ones the switches, the
+//! OLED and the TRRS jack are wired to in the KiCad schematic.
+//!
+//! usage: pinmap-check [main.rs] [kicad_sch]
+
+use {
+    std::{env, fs, process::exit},
+    yuki_tools::{pinmap, sch},
+};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let main = args.next().unwrap_or("src/main.rs".into());
+    let schematic = args.next().unwrap_or("yuki_pcb/yuki_pcb.kicad_sch".into());
+
+    let read = |path: &str| fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        exit(1)
+    });
+
+    let fw = pinmap::firmware(&read(&main)).unwrap_or_else(|e| {
+        eprintln!("{}: {}", main, e);
+        exit(1)
+    });
+
+    let errors = sch::pins(&read(&schematic))
+        .and_then(|pins| pinmap::check(&fw, &pins))
+        .unwrap_or_else(|e| {
+            eprintln!("{}: {}", schematic, e);
+            exit(1)
+        });
+
+    if errors.is_empty() {
+        println!("{} matches {}", main, schematic);
+        return;
+    }
+
+    for e in &errors {
+        eprintln!("{}", e);
+    }
+    exit(1)
+}
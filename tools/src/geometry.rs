@@ -0,0 +1,42 @@
+/// A physical key, `x`/`y` is its center in mm and `angle` its
+/// clockwise rotation in degrees. See `pcb::matrix` for reading
+/// one half from the pcb.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+    pub row: usize,
+    pub col: usize,
+    pub x: f64,
+    pub y: f64,
+    pub angle: f64,
+}
+
+// gap between the innermost thumb keys of the two halves
+const GAP: f64 = 50.0;
+
+/// Both halves with the keys at their `layout!` coordinates, the right
+/// half is the mirror of `half` with its columns counted from the right.
+pub fn keyboard(half: &[Key]) -> Vec<Key> {
+    let (min_x, max_x) = bounds(half.iter().map(|k| k.x));
+    let (min_y, _) = bounds(half.iter().map(|k| k.y));
+    let mirror = 2.0 * max_x + GAP;
+
+    let left = half.iter().map(|k| Key {
+        x: k.x - min_x,
+        y: k.y - min_y,
+        ..*k
+    });
+
+    let right = half.iter().map(|k| Key {
+        col: 11 - k.col,
+        x: mirror - k.x - min_x,
+        y: k.y - min_y,
+        angle: -k.angle + 0.0,
+        ..*k
+    });
+
+    left.chain(right).collect()
+}
+
+fn bounds(v: impl Iterator<Item = f64>) -> (f64, f64) {
+    v.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)))
+}
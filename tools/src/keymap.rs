@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+/// Keymap read from the source of yuki-core/src/layout.rs.
+pub struct Keymap {
+    pub layers: Vec<Layer>,
+    /// `const` and `ma!` definitions, name to expression.
+    pub consts: HashMap<String, String>,
+}
+
+pub struct Layer {
+    pub name: String,
+    /// The tokens of the `layout!` macro, `rows[i][j]`.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Keymap {
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let start = src.find("layout! {").ok_or("no `layout!` block found")?;
+        let body = &src[start + "layout! ".len()..];
+        let body = &body[..closing(body, '{', '}').ok_or("unclosed `layout!` block")?];
+
+        Ok(Self {
+            layers: parse_layers(&body[1..])?,
+            consts: parse_consts(src),
+        })
+    }
+
+    /// Expression of a `const` or `ma!` definition, following aliases.
+    pub fn resolve<'a>(&'a self, mut name: &'a str) -> Option<&'a str> {
+        for _ in 0..8 {
+            let expr = self.consts.get(name)?;
+            match self.consts.contains_key(expr.as_str()) {
+                true => name = expr,
+                false => return Some(expr),
+            }
+        }
+        None
+    }
+}
+
+fn parse_layers(body: &str) -> Result<Vec<Layer>, String> {
+    let mut layers = Vec::new();
+    let mut name = String::new();
+    let mut rest = body;
+
+    loop {
+        rest = rest.trim_start();
+
+        if rest.is_empty() {
+            return Ok(layers);
+        } else if let Some(comment) = rest.strip_prefix("//") {
+            let end = comment.find('\n').unwrap_or(comment.len());
+            name = comment[..end].trim().to_string();
+            rest = &comment[end..];
+        } else if rest.starts_with('{') {
+            let end = closing(rest, '{', '}').ok_or("unclosed layer")?;
+            layers.push(Layer {
+                name: std::mem::take(&mut name),
+                rows: parse_rows(&rest[1..end])?,
+            });
+            rest = &rest[end + 1..];
+        } else {
+            return Err(format!("unexpected `{}` between layers", first_line(rest)));
+        }
+    }
+}
+
+fn parse_rows(layer: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut rest = layer;
+
+    loop {
+        rest = rest.trim_start();
+
+        if rest.is_empty() {
+            return Ok(rows);
+        } else if rest.starts_with('[') {
+            let end = closing(rest, '[', ']').ok_or("unclosed row")?;
+            rows.push(tokens(&rest[1..end])?);
+            rest = &rest[end + 1..];
+        } else {
+            return Err(format!("unexpected `{}` in layer", first_line(rest)));
+        }
+    }
+}
+
+// a key is an identifier, `(n)` or a `{ expression }`
+fn tokens(row: &str) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    let mut rest = row;
+
+    loop {
+        rest = rest.trim_start();
+
+        let end = match rest.chars().next() {
+            None => return Ok(keys),
+            Some('{') => closing(rest, '{', '}').map(|e| e + 1),
+            Some('(') => closing(rest, '(', ')').map(|e| e + 1),
+            Some(_) => Some(rest.find(char::is_whitespace).unwrap_or(rest.len())),
+        }
+        .ok_or_else(|| format!("unclosed key `{}`", first_line(rest)))?;
+
+        keys.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+}
+
+fn parse_consts(src: &str) -> HashMap<String, String> {
+    let mut consts = HashMap::new();
+
+    for (i, _) in src.match_indices("const ") {
+        let def = &src[i + "const ".len()..];
+        let (Some(colon), Some(eq)) = (def.find(':'), def.find(" = ")) else { continue };
+        if colon > eq {
+            continue;
+        }
+
+        let Some(end) = statement_end(&def[eq..]) else { continue };
+        consts.insert(
+            def[..colon].trim().to_string(),
+            def[eq + 3..eq + end].trim().to_string(),
+        );
+    }
+
+    for (i, _) in src.match_indices("\nma!(") {
+        let def = &src[i + "\nma!(".len()..];
+        let Some(end) = def.find(");") else { continue };
+        let Some((name, action)) = def[..end].split_once(',') else { continue };
+
+        consts.insert(
+            name.trim().to_string(),
+            format!("Custom(CustomAction::M({}))", action.trim()),
+        );
+    }
+
+    consts
+}
+
+// index of the `;` ending a statement, skipping the nested ones
+fn statement_end(s: &str) -> Option<usize> {
+    let mut depth = 0i32;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ';' if depth == 0 => return Some(i),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+// index of the bracket closing the one `s` starts with
+fn closing(s: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+
+    for (i, c) in s.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+
+    None
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("").trim()
+}
@@ -0,0 +1,47 @@
+use crate::{geometry::Key, labels::Label};
+
+// keyboard-layout-editor units, the column and row pitch of the pcb
+const UNIT_X: f64 = 19.0;
+const UNIT_Y: f64 = 17.75;
+
+/// KLE raw data with one row per key, each key is rotated around its center.
+pub fn render(name: &str, keys: &[(Key, Label)]) -> String {
+    let mut out = format!("[\n  {{\"name\": {}}}", string(name));
+
+    for (key, label) in keys {
+        let legend = match &label.hold {
+            Some(hold) => format!("{}\n\n\n\n\n\n{}", label.tap, hold),
+            None => label.tap.clone(),
+        };
+
+        out += &format!(
+            ",\n  [{{\"r\": {}, \"rx\": {}, \"ry\": {}, \"x\": -0.5, \"y\": -0.5}}, {}]",
+            num(key.angle),
+            num(key.x / UNIT_X),
+            num(key.y / UNIT_Y),
+            string(&legend),
+        );
+    }
+
+    out + "\n]\n"
+}
+
+fn num(v: f64) -> String {
+    let v = (v * 1000.0).round() / 1000.0;
+    format!("{}", v)
+}
+
+pub fn string(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            c => out.push(c),
+        }
+    }
+
+    out + "\""
+}
@@ -0,0 +1,287 @@
+use crate::keymap::Keymap;
+
+/// Legend of a key, `hold` is set for hold-taps.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Label {
+    pub tap: String,
+    pub hold: Option<String>,
+    pub kind: Kind,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Kind {
+    #[default]
+    Key,
+    None,
+    Trans,
+    Layer,
+    Mouse,
+    Custom,
+}
+
+impl Label {
+    fn new(tap: impl Into<String>, kind: Kind) -> Self {
+        Self { tap: tap.into(), hold: None, kind }
+    }
+}
+
+/// Label of a token from the `layout!` macro.
+pub fn label(token: &str, keymap: &Keymap) -> Label {
+    match token {
+        "n" => Label::new("", Kind::None),
+        "t" => Label::new("▽", Kind::Trans),
+        _ if token.starts_with('(') => Label::new(format!("L{}", inner(token)), Kind::Layer),
+        _ if token.starts_with('{') => expr(inner(token), keymap, 0),
+        _ => Label::new(keycode(token), Kind::Key),
+    }
+}
+
+fn expr(e: &str, keymap: &Keymap, depth: u8) -> Label {
+    let e = e.trim();
+
+    if depth > 8 {
+        return Label::new(e, Kind::Custom);
+    }
+
+    if let Some(def) = keymap.resolve(e) {
+        return expr(def, keymap, depth + 1);
+    }
+
+    if let Some(k) = call(e, "k(") {
+        Label::new(keycode(k), Kind::Key)
+    } else if let Some(k) = call(e, "s!(") {
+        Label::new(shifted(k), Kind::Key)
+    } else if let Some(keys) = call(e, "m(") {
+        Label::new(combo(keys), Kind::Key)
+    } else if e.starts_with("HoldTap(") {
+        let part = |name| field(e, name).map(|f| expr(f, keymap, depth + 1).tap);
+        Label {
+            tap: part("tap:").unwrap_or_default(),
+            hold: part("hold:"),
+            kind: Kind::Key,
+        }
+    } else if let Some(custom) = call(e, "Custom(") {
+        custom_label(custom.trim_start_matches("CustomAction::"))
+    } else if let Some(layer) = call(e, "Action::DefaultLayer(").or(call(e, "DefaultLayer(")) {
+        Label::new(format!("Base {}", layer), Kind::Layer)
+    } else if let Some(layer) = call(e, "Action::Layer(").or(call(e, "Layer(")) {
+        Label::new(format!("L{}", layer), Kind::Layer)
+    } else {
+        Label::new(e, Kind::Custom)
+    }
+}
+
+fn custom_label(c: &str) -> Label {
+    let custom = |l: &str| Label::new(l, Kind::Custom);
+
+    if let Some(m) = call(c, "M(") {
+        return Label::new(mouse(m.trim_start_matches("MAction::")), Kind::Mouse);
+    }
+
+    if let Some(layer) = call(c, "DefaultLayer(") {
+        return Label::new(format!("Base {}", layer), Kind::Layer);
+    }
+
+    if let Some(sc) = call(c, "Shortcut(") {
+        return custom(&title(sc.trim_start_matches('&').trim_end_matches("_SC")));
+    }
+
+    match variant(c) {
+        "AS" => match variant(call(c, "AS(").unwrap_or("")) {
+            "Toggle" => custom("AutoShift"),
+            "Increase" => custom("AS +"),
+            "Decrease" => custom("AS -"),
+            v => custom(v),
+        },
+        "HT" => match variant(call(c, "HT(").unwrap_or("")) {
+            "Select" => custom("HT Sel"),
+            "Increase" => custom("HT +"),
+            "Decrease" => custom("HT -"),
+            v => custom(v),
+        },
+        "Swap" => match variant(call(c, "Swap(").unwrap_or("")) {
+            "Momentary" => custom("Swap"),
+            "Toggle" => custom("Swap Tog"),
+            "OneShot" => custom("Swap 1x"),
+            v => custom(v),
+        },
+        "Pointing" => match variant(call(c, "Pointing(").unwrap_or("")) {
+            "Sniping" => Label::new("Snipe", Kind::Mouse),
+            "DragScroll" => Label::new("Drag Scr", Kind::Mouse),
+            v => custom(v),
+        },
+        "AltRepeat" => custom("Alt Rep"),
+        "Os" => custom("OS"),
+        "Bootloader" => custom("Boot"),
+        "Nkro" => custom("NKRO"),
+        v => custom(v),
+    }
+}
+
+fn mouse(m: &str) -> String {
+    if let Some(d) = call(m, "Move(") {
+        format!("Ms {}", arrow(variant(d)))
+    } else if let Some(d) = call(m, "Scroll(") {
+        format!("Wh {}", arrow(variant(d)))
+    } else if let Some(n) = call(m, "Warp(") {
+        format!("Warp {}x{}", n, n)
+    } else if let Some(speed) = call(m, "ToggleSpeed(") {
+        format!("{} Tgl", speed_mode(variant(speed)))
+    } else {
+        match variant(m) {
+            "Left" => "Click L".into(),
+            "Right" => "Click R".into(),
+            "Middle" => "Click M".into(),
+            "Back" => "Ms Back".into(),
+            "Forward" => "Ms Fwd".into(),
+            "DragLock" => "Drag".into(),
+            "Speedup" => "Ms Fast".into(),
+            "Precision" => "Ms Slow".into(),
+            "ToggleActive" => "Ms On".into(),
+            v => v.into(),
+        }
+    }
+}
+
+fn speed_mode(speed: &str) -> &str {
+    match speed {
+        "Precision" => "Slow",
+        "Turbo" => "Fast",
+        s => s,
+    }
+}
+
+fn arrow(dir: &str) -> &str {
+    match dir {
+        "Up" => "↑",
+        "Down" => "↓",
+        "Left" => "←",
+        "Right" => "→",
+        d => d,
+    }
+}
+
+fn combo(keys: &str) -> String {
+    let keys = keys.trim_start_matches('&').trim_start_matches('[');
+    let keys = keys.split(']').next().unwrap_or("");
+
+    keys.split(',')
+        .map(|k| keycode(k.trim()))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Label of a keyberon `KeyCode` variant.
+pub fn keycode(k: &str) -> String {
+    let l = match k {
+        "Grave" => "`",
+        "Quote" => "'",
+        "Comma" => ",",
+        "Dot" => ".",
+        "Slash" => "/",
+        "SColon" => ";",
+        "Minus" => "-",
+        "Equal" => "=",
+        "LBracket" => "[",
+        "RBracket" => "]",
+        "Bslash" => "\\",
+        "Escape" => "Esc",
+        "BSpace" => "Bksp",
+        "Delete" => "Del",
+        "Insert" => "Ins",
+        "PScreen" => "PrtSc",
+        "CapsLock" => "Caps",
+        "PgDown" => "PgDn",
+        "LShift" | "RShift" => "Shift",
+        "LCtrl" | "RCtrl" => "Ctrl",
+        "LAlt" | "RAlt" => "Alt",
+        "LGui" | "RGui" => "Gui",
+        "Up" => "↑",
+        "Down" => "↓",
+        "Left" => "←",
+        "Right" => "→",
+        "MediaNextSong" => "Next",
+        "MediaPreviousSong" => "Prev",
+        "MediaPlayPause" => "Play",
+        "VolUp" => "Vol+",
+        "VolDown" => "Vol-",
+        _ => return k.strip_prefix("Kb").unwrap_or(k).to_string(),
+    };
+
+    l.to_string()
+}
+
+fn shifted(k: &str) -> String {
+    let l = match k {
+        "Kb1" => "!",
+        "Kb2" => "@",
+        "Kb3" => "#",
+        "Kb4" => "$",
+        "Kb5" => "%",
+        "Kb6" => "^",
+        "Kb7" => "&",
+        "Kb8" => "*",
+        "Kb9" => "(",
+        "Kb0" => ")",
+        "Grave" => "~",
+        "Quote" => "\"",
+        "Comma" => "<",
+        "Dot" => ">",
+        "Slash" => "?",
+        "SColon" => ":",
+        "Minus" => "_",
+        "Equal" => "+",
+        "LBracket" => "{",
+        "RBracket" => "}",
+        "Bslash" => "|",
+        _ if k.len() == 1 => return k.to_uppercase(),
+        _ => return format!("⇧{}", keycode(k)),
+    };
+
+    l.to_string()
+}
+
+// the arguments of `e` if its a call to `f`
+fn call<'a>(e: &'a str, f: &str) -> Option<&'a str> {
+    e.strip_prefix(f)?.strip_suffix(')').map(str::trim)
+}
+
+// `Name` of `Name(..)` or `Path::Name`
+fn variant(e: &str) -> &str {
+    let e = e.split('(').next().unwrap_or(e);
+    e.rsplit("::").next().unwrap_or(e).trim()
+}
+
+// the value of `name: value,` in a struct literal
+fn field<'a>(e: &'a str, name: &str) -> Option<&'a str> {
+    let value = &e[e.find(name)? + name.len()..];
+    let mut depth = 0i32;
+
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => return Some(value[..i].trim()),
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => return Some(value[..i].trim()),
+            _ => (),
+        }
+    }
+
+    Some(value.trim())
+}
+
+fn inner(token: &str) -> &str {
+    &token[1..token.len() - 1]
+}
+
+fn title(name: &str) -> String {
+    name.split('_')
+        .map(|w| {
+            let mut c = w.chars();
+            c.next()
+                .map(|f| f.to_uppercase().chain(c.flat_map(char::to_lowercase)).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
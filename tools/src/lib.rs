@@ -0,0 +1,25 @@
+pub mod geometry;
+pub mod keymap;
+pub mod kle;
+pub mod labels;
+pub mod pcb;
+pub mod pinmap;
+pub mod sch;
+pub mod sexpr;
+pub mod svg;
+
+use {
+    geometry::Key,
+    keymap::{Keymap, Layer},
+    labels::Label,
+};
+
+/// The keys of `layer` that exist on the board, with their labels.
+pub fn layer_keys(layer: &Layer, keymap: &Keymap, keys: &[Key]) -> Vec<(Key, Label)> {
+    keys.iter()
+        .filter_map(|k| {
+            let token = layer.rows.get(k.row)?.get(k.col)?;
+            Some((*k, labels::label(token, keymap)))
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+use crate::{geometry::Key, sexpr::Sexpr};
+
+/// A key switch footprint from a .kicad_pcb file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Switch {
+    /// `K<row><col>` on the yuki pcb.
+    pub reference: String,
+    /// Net of the switch pin that is not GND.
+    pub net: String,
+    pub x: f64,
+    pub y: f64,
+    /// Counterclockwise, as KiCad stores it.
+    pub angle: f64,
+}
+
+// footprints that are key switches
+const SWITCH_FOOTPRINTS: &[&str] = &["Kailh_Choc", "SW_MX", "Cherry_MX"];
+
+pub fn switches(pcb: &str) -> Result<Vec<Switch>, String> {
+    let root = Sexpr::parse(pcb)?;
+    if root.name() != Some("kicad_pcb") {
+        return Err("not a kicad_pcb file".into());
+    }
+
+    Ok(root.children("footprint").filter_map(switch).collect())
+}
+
+fn switch(fp: &Sexpr) -> Option<Switch> {
+    let lib = fp.arg(0)?;
+    if !SWITCH_FOOTPRINTS.iter().any(|s| lib.contains(s)) {
+        return None;
+    }
+
+    let at = fp.child("at")?;
+    let reference = fp
+        .children("property")
+        .find(|p| p.arg(0) == Some("Reference"))?
+        .arg(1)?
+        .to_string();
+
+    let net = fp
+        .children("pad")
+        .filter_map(|p| p.child("net")?.arg(1))
+        .find(|n| *n != "GND")
+        .unwrap_or_default()
+        .to_string();
+
+    Some(Switch {
+        reference,
+        net,
+        x: at.arg(0)?.parse().ok()?,
+        y: at.arg(1)?.parse().ok()?,
+        angle: at.arg(2).and_then(|a| a.parse().ok()).unwrap_or(0.0),
+    })
+}
+
+/// Places the switches in the matrix by their reference, see `matrix_coords`.
+pub fn matrix(switches: &[Switch]) -> Result<Vec<Key>, String> {
+    let refs: Vec<&str> = switches.iter().map(|s| s.reference.as_str()).collect();
+
+    Ok(matrix_coords(&refs)?
+        .into_iter()
+        .zip(switches)
+        .map(|((row, col), s)| Key {
+            row,
+            col,
+            x: s.x,
+            y: s.y,
+            // + 0.0 turns -0.0 into 0.0
+            angle: -s.angle + 0.0,
+        })
+        .collect())
+}
+
+/// Matrix (row, col) of switches named `K<row><col>`. Rows with less keys
+/// than the widest one are aligned to its last columns, like the thumb
+/// row which starts at column 2.
+pub fn matrix_coords(references: &[&str]) -> Result<Vec<(usize, usize)>, String> {
+    let rc = references
+        .iter()
+        .map(|r| r.strip_prefix('K')
+            .filter(|rc| rc.len() == 2)
+            .and_then(|rc| Some((rc[..1].parse::<usize>().ok()?, rc[1..].parse::<usize>().ok()?)))
+            .ok_or_else(|| format!("switch `{}` is not named K<row><col>", r)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cols = |row| rc.iter().filter(|(r, _)| *r == row).count();
+    let width = rc.iter().map(|(r, _)| cols(*r)).max().unwrap_or(0);
+
+    Ok(rc.iter().map(|&(row, col)| (row, col + width - cols(row))).collect())
+}
@@ -0,0 +1,159 @@
+use crate::{pcb, sch::Pin};
+
+/// The MCU pins the firmware uses, by name (`PA9`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FirmwarePins {
+    /// The `DirectPinMatrix` by (row, col).
+    pub matrix: Vec<Vec<Option<String>>>,
+    pub scl: Option<String>,
+    pub sda: Option<String>,
+    pub tx: Option<String>,
+    pub rx: Option<String>,
+}
+
+/// Reads the pins from the `init` of src/main.rs.
+pub fn firmware(main: &str) -> Result<FirmwarePins, String> {
+    let matrix = call_args(main, "DirectPinMatrix::new(")
+        .ok_or("no `DirectPinMatrix::new` call")?;
+
+    let matrix = split(matrix.trim().strip_prefix('[').and_then(|m| m.strip_suffix(']')).unwrap_or(matrix))
+        .into_iter()
+        .map(|row| {
+            let row = row.trim().strip_prefix('[').and_then(|r| r.strip_suffix(']'))
+                .ok_or_else(|| format!("matrix row `{}` is not an array", row.trim()))?;
+
+            Ok(split(row).into_iter().map(pin_name).collect())
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // the first two pins passed are (scl, sda) and (tx, rx)
+    let two = |call| {
+        let pins: Vec<_> = call_args(main, call)
+            .map(|a| a.split(|c: char| !c.is_alphanumeric()).filter_map(pin_name).take(2).collect())
+            .unwrap_or_default();
+        (pins.first().cloned(), pins.get(1).cloned())
+    };
+    let (scl, sda) = two("OLED::new(");
+    let (tx, rx) = two("Serial::new(");
+
+    Ok(FirmwarePins { matrix, scl, sda, tx, rx })
+}
+
+/// Compares the firmware pins with the schematic, one message per mismatch.
+pub fn check(fw: &FirmwarePins, sch: &[Pin]) -> Result<Vec<String>, String> {
+    let mcu = sch.iter()
+        .find(|p| p.name == "PA0")
+        .map(|p| p.symbol.as_str())
+        .ok_or("no BlackPill in the schematic")?;
+
+    // MCU pin on a net
+    let mcu_pin = |net: &str| sch.iter()
+        .find(|p| p.symbol == mcu && p.net == net)
+        .map(|p| p.name.clone());
+
+    let mut errors = Vec::new();
+    let mut compare = |what: String, fw: Option<&String>, sch: Option<String>| {
+        if fw != sch.as_ref() {
+            let name = |p: Option<&String>| p.cloned().unwrap_or_else(|| "no pin".into());
+            errors.push(format!("{}: firmware uses {}, schematic has {}", what, name(fw), name(sch.as_ref())));
+        }
+    };
+
+    let mut switches: Vec<&str> = sch.iter()
+        .map(|p| p.symbol.as_str())
+        .filter(|s| s.starts_with('K'))
+        .collect();
+    switches.sort();
+    switches.dedup();
+
+    let coords = pcb::matrix_coords(&switches)?;
+    let rows = coords.iter().map(|c| c.0 + 1).max().unwrap_or(0).max(fw.matrix.len());
+    let cols = coords.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+
+    for row in 0..rows {
+        let fw_cols = fw.matrix.get(row).map_or(0, Vec::len);
+        for col in 0..cols.max(fw_cols) {
+            let switch = coords.iter().position(|&c| c == (row, col)).map(|i| switches[i]);
+
+            // the switch pin that is not on GND goes to the MCU
+            let pin = switch.and_then(|s| sch.iter()
+                .filter(|p| p.symbol == s && p.net != "GND")
+                .find_map(|p| mcu_pin(&p.net)));
+
+            let what = match switch {
+                Some(s) => format!("key ({}, {}) {}", row, col, s),
+                None => format!("key ({}, {})", row, col),
+            };
+            compare(what, fw.matrix.get(row).and_then(|r| r.get(col)).and_then(Option::as_ref), pin);
+        }
+    }
+
+    // the oled pins by name, the serial link by the nets of its labels
+    let oled = |name: &str| sch.iter()
+        .find(|p| p.symbol.starts_with("DS") && p.name == name)
+        .and_then(|p| mcu_pin(&p.net));
+
+    compare("OLED SCL".into(), fw.scl.as_ref(), oled("SCL"));
+    compare("OLED SDA".into(), fw.sda.as_ref(), oled("SDA"));
+    compare("USART TX".into(), fw.tx.as_ref(), mcu_pin("TX"));
+    compare("USART RX".into(), fw.rx.as_ref(), mcu_pin("RX"));
+
+    for net in ["TX", "RX"] {
+        if !sch.iter().any(|p| p.net == net && ["RING1", "RING2", "TIP", "SLEEVE"].contains(&p.name.as_str())) {
+            errors.push(format!("USART {}: not connected to the TRRS jack", net));
+        }
+    }
+
+    Ok(errors)
+}
+
+// the arguments of the first call to `call`, without the parentheses
+fn call_args<'a>(src: &'a str, call: &str) -> Option<&'a str> {
+    let start = src.find(call)? + call.len();
+    let mut depth = 1;
+
+    for (i, c) in src[start..].char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 {
+            return Some(&src[start..start + i]);
+        }
+    }
+    None
+}
+
+// splits on the top level commas, skips trailing ones
+fn split(src: &str) -> Vec<&str> {
+    let (mut parts, mut depth, mut start) = (Vec::new(), 0, 0);
+
+    for (i, c) in src.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&src[start..i]);
+                start = i + 1;
+            },
+            _ => (),
+        }
+    }
+    parts.push(&src[start..]);
+    parts.retain(|p| !p.trim().is_empty());
+    parts
+}
+
+// `pa9` in `Some(gpioa.pa9.into_pull_up_input().erase())`, `None` for `None`
+fn pin_name(src: &str) -> Option<String> {
+    src.split(|c: char| !c.is_alphanumeric())
+        .find(|w| {
+            let mut c = w.chars();
+            c.next() == Some('p')
+                && c.next().is_some_and(|port| ('a'..='h').contains(&port))
+                && !c.as_str().is_empty()
+                && c.as_str().chars().all(|c| c.is_ascii_digit())
+        })
+        .map(str::to_uppercase)
+}
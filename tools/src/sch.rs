@@ -0,0 +1,216 @@
+use {
+    std::collections::HashMap,
+    crate::sexpr::Sexpr,
+};
+
+/// A pin of a placed symbol and the net it is connected to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pin {
+    /// Reference of the symbol, `U1`, `K00`..
+    pub symbol: String,
+    pub number: String,
+    pub name: String,
+    pub net: String,
+}
+
+struct LibPin {
+    number: String,
+    name: String,
+    x: f64,
+    y: f64,
+}
+
+struct LibSymbol {
+    power: bool,
+    pins: Vec<LibPin>,
+}
+
+// points are compared in 1/100 mm
+type Point = (i64, i64);
+
+fn point(x: f64, y: f64) -> Point {
+    ((x * 100.0).round() as i64, (y * 100.0).round() as i64)
+}
+
+/// Pins of every symbol in a .kicad_sch file with their nets. Nets are
+/// named after their label or power symbol, unnamed ones get `Net-<n>`.
+pub fn pins(sch: &str) -> Result<Vec<Pin>, String> {
+    let root = Sexpr::parse(sch)?;
+    if root.name() != Some("kicad_sch") {
+        return Err("not a kicad_sch file".into());
+    }
+
+    let lib: HashMap<&str, LibSymbol> = root
+        .child("lib_symbols")
+        .map(|l| l.children("symbol").filter_map(|s| Some((s.arg(0)?, lib_symbol(s)))).collect())
+        .unwrap_or_default();
+
+    let mut nets = Nets::default();
+
+    for wire in root.children("wire") {
+        let pts: Vec<Point> = wire
+            .child("pts")
+            .map(|p| p.children("xy").filter_map(xy).collect())
+            .unwrap_or_default();
+
+        for w in pts.windows(2) {
+            nets.wires.push((w[0], w[1]));
+            nets.union(w[0], w[1]);
+        }
+    }
+
+    for label in root.children("label").chain(root.children("global_label")) {
+        if let (Some(name), Some(at)) = (label.arg(0), label.child("at").and_then(xy)) {
+            nets.name(at, name);
+        }
+    }
+
+    // (reference, lib pin, position)
+    let mut placed = Vec::new();
+
+    for sym in root.children("symbol") {
+        let Some(lib_sym) = sym.child("lib_id").and_then(|l| l.arg(0)).and_then(|id| lib.get(id)) else { continue };
+        let Some(at) = sym.child("at") else { continue };
+        let num = |i| at.arg(i).and_then(|a: &str| a.parse::<f64>().ok()).unwrap_or(0.0);
+        let (x, y, rot) = (num(0), num(1), num(2));
+        let mirror = sym.child("mirror").and_then(|m| m.arg(0));
+
+        let property = |name| sym
+            .children("property")
+            .find(|p| p.arg(0) == Some(name))
+            .and_then(|p| p.arg(1))
+            .unwrap_or_default();
+
+        for pin in &lib_sym.pins {
+            let pos = transform((pin.x, pin.y), rot, mirror);
+            let pos = point(x + pos.0, y + pos.1);
+
+            match lib_sym.power {
+                true => nets.name(pos, property("Value")),
+                false => placed.push((property("Reference"), pin, pos)),
+            }
+        }
+    }
+
+    Ok(placed
+        .into_iter()
+        .map(|(symbol, pin, pos)| Pin {
+            symbol: symbol.to_string(),
+            number: pin.number.clone(),
+            name: pin.name.clone(),
+            net: nets.net_name(pos),
+        })
+        .collect())
+}
+
+fn lib_symbol(sym: &Sexpr) -> LibSymbol {
+    let mut pins = Vec::new();
+    collect_pins(sym, &mut pins);
+
+    LibSymbol {
+        power: sym.child("power").is_some(),
+        pins,
+    }
+}
+
+// pins are in the unit sub symbols
+fn collect_pins(s: &Sexpr, pins: &mut Vec<LibPin>) {
+    for child in s.args() {
+        match child.name() {
+            Some("symbol") => collect_pins(child, pins),
+            Some("pin") => {
+                let text = |name| child.child(name).and_then(|n| n.arg(0)).unwrap_or_default().to_string();
+                let at = child.child("at");
+                let num = |i| at.and_then(|a| a.arg(i)).and_then(|a| a.parse().ok()).unwrap_or(0.0);
+
+                pins.push(LibPin {
+                    number: text("number"),
+                    name: text("name"),
+                    x: num(0),
+                    y: num(1),
+                });
+            },
+            _ => (),
+        }
+    }
+}
+
+// library symbols have y pointing up, the sheet has it pointing down
+fn transform((x, y): (f64, f64), rot: f64, mirror: Option<&str>) -> (f64, f64) {
+    let (x, y) = (x, -y);
+    let (sin, cos) = rot.to_radians().sin_cos();
+    let (x, y) = (x * cos + y * sin, -x * sin + y * cos);
+
+    match mirror {
+        Some("x") => (x, -y),
+        Some("y") => (-x, y),
+        _ => (x, y),
+    }
+}
+
+fn xy(s: &Sexpr) -> Option<Point> {
+    Some(point(s.arg(0)?.parse().ok()?, s.arg(1)?.parse().ok()?))
+}
+
+#[derive(Default)]
+struct Nets {
+    parent: HashMap<Point, Point>,
+    names: Vec<(Point, String)>,
+    wires: Vec<(Point, Point)>,
+}
+
+impl Nets {
+    fn find(&mut self, p: Point) -> Point {
+        let parent = *self.parent.entry(p).or_insert(p);
+        if parent == p {
+            return p;
+        }
+
+        let root = self.find(parent);
+        self.parent.insert(p, root);
+        root
+    }
+
+    fn union(&mut self, a: Point, b: Point) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent.insert(a, b);
+        }
+    }
+
+    // labels and pins also connect to the middle of a wire
+    fn attach(&mut self, p: Point) {
+        let on: Vec<Point> = self.wires.iter().filter(|(a, b)| on_segment(p, *a, *b)).map(|(a, _)| *a).collect();
+        on.into_iter().for_each(|a| self.union(p, a));
+    }
+
+    fn name(&mut self, p: Point, name: &str) {
+        self.attach(p);
+
+        // same name, same net
+        if let Some(&(other, _)) = self.names.iter().find(|(_, n)| n == name) {
+            self.union(p, other);
+        }
+        self.names.push((p, name.to_string()));
+    }
+
+    fn net_name(&mut self, p: Point) -> String {
+        self.attach(p);
+        let root = self.find(p);
+
+        let names: Vec<(Point, String)> = self.names.clone();
+        names
+            .into_iter()
+            .find(|(n, _)| self.find(*n) == root)
+            .map(|(_, name)| name)
+            .unwrap_or_else(|| format!("Net-{}-{}", root.0, root.1))
+    }
+}
+
+fn on_segment(p: Point, a: Point, b: Point) -> bool {
+    let cross = (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+
+    cross == 0 &&
+    p.0 >= a.0.min(b.0) && p.0 <= a.0.max(b.0) &&
+    p.1 >= a.1.min(b.1) && p.1 <= a.1.max(b.1)
+}
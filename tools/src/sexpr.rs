@@ -0,0 +1,94 @@
+/// S-expression as used by the KiCad file formats.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sexpr {
+    Atom(String),
+    List(Vec<Sexpr>),
+}
+
+impl Sexpr {
+    pub fn parse(src: &str) -> Result<Self, String> {
+        let mut chars = src.char_indices().peekable();
+        let mut stack: Vec<Vec<Sexpr>> = vec![Vec::new()];
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '(' => stack.push(Vec::new()),
+                ')' => {
+                    let list = stack.pop().filter(|_| !stack.is_empty())
+                        .ok_or_else(|| format!("unexpected `)` at {}", i))?;
+                    stack.last_mut().unwrap().push(Sexpr::List(list));
+                },
+                '"' => {
+                    let mut s = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.next() {
+                                Some((_, 'n')) => s.push('\n'),
+                                Some((_, c)) => s.push(c),
+                                None => break,
+                            },
+                            Some((_, c)) => s.push(c),
+                            None => return Err(format!("unclosed string at {}", i)),
+                        }
+                    }
+                    stack.last_mut().unwrap().push(Sexpr::Atom(s));
+                },
+                c if c.is_whitespace() => (),
+                c => {
+                    let mut s = String::from(c);
+                    while let Some(&(_, c)) = chars.peek() {
+                        if c.is_whitespace() || c == '(' || c == ')' {
+                            break;
+                        }
+                        s.push(c);
+                        chars.next();
+                    }
+                    stack.last_mut().unwrap().push(Sexpr::Atom(s));
+                },
+            }
+        }
+
+        match (stack.pop(), stack.is_empty()) {
+            (Some(mut top), true) if top.len() == 1 => Ok(top.remove(0)),
+            (Some(_), true) => Err("expected a single top level list".into()),
+            _ => Err("unclosed `(`".into()),
+        }
+    }
+
+    pub fn atom(&self) -> Option<&str> {
+        match self {
+            Sexpr::Atom(a) => Some(a),
+            Sexpr::List(_) => None,
+        }
+    }
+
+    /// Name of a list, its first atom.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Sexpr::List(l) => l.first()?.atom(),
+            Sexpr::Atom(_) => None,
+        }
+    }
+
+    /// Elements after the name.
+    pub fn args(&self) -> &[Sexpr] {
+        match self {
+            Sexpr::List(l) if !l.is_empty() => &l[1..],
+            _ => &[],
+        }
+    }
+
+    pub fn arg(&self, i: usize) -> Option<&str> {
+        self.args().get(i)?.atom()
+    }
+
+    /// Direct children named `name`.
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Sexpr> {
+        self.args().iter().filter(move |s| s.name() == Some(name))
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Sexpr> {
+        self.args().iter().find(|s| s.name() == Some(name))
+    }
+}
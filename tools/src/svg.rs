@@ -0,0 +1,99 @@
+use crate::{
+    geometry::Key,
+    labels::{Kind, Label},
+};
+
+// choc keycap size in mm
+const CAP_W: f64 = 17.5;
+const CAP_H: f64 = 16.5;
+const MARGIN: f64 = 12.0;
+const TITLE: f64 = 10.0;
+
+/// One layer drawn on the physical key positions.
+pub fn render(name: &str, keys: &[(Key, Label)]) -> String {
+    let width = keys.iter().map(|(k, _)| k.x).fold(0.0, f64::max) + 2.0 * MARGIN;
+    let height = keys.iter().map(|(k, _)| k.y).fold(0.0, f64::max) + 2.0 * MARGIN + TITLE;
+
+    let mut out = format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}mm\" height=\"{h}mm\" ",
+            "font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"middle\">\n",
+            "  <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n",
+            "  <text x=\"{x}\" y=\"{y}\" font-size=\"6\" font-weight=\"bold\">{name}</text>\n",
+        ),
+        w = width,
+        h = height,
+        x = width / 2.0,
+        y = MARGIN / 2.0 + 2.0,
+        name = escape(name),
+    );
+
+    for (key, label) in keys {
+        out += &format!(
+            "  <g transform=\"translate({} {}) rotate({})\">\n",
+            key.x + MARGIN,
+            key.y + MARGIN + TITLE,
+            key.angle,
+        );
+        out += &format!(
+            "    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"2\" fill=\"{}\" stroke=\"#444444\" stroke-width=\"0.4\"/>\n",
+            -CAP_W / 2.0,
+            -CAP_H / 2.0,
+            CAP_W,
+            CAP_H,
+            fill(label.kind),
+        );
+
+        match &label.hold {
+            Some(hold) => {
+                out += &text(&label.tap, -2.5, font_size(&label.tap), "#000000");
+                out += &text(hold, 4.5, 2.5, "#666666");
+            },
+            None => out += &text(&label.tap, 0.0, font_size(&label.tap), text_color(label.kind)),
+        }
+
+        out += "  </g>\n";
+    }
+
+    out + "</svg>\n"
+}
+
+fn text(s: &str, y: f64, size: f64, color: &str) -> String {
+    match s.is_empty() {
+        true => String::new(),
+        false => format!(
+            "    <text y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+            y, size, color, escape(s)
+        ),
+    }
+}
+
+fn font_size(s: &str) -> f64 {
+    match s.chars().count() {
+        0..=2 => 5.0,
+        3..=4 => 3.8,
+        _ => 2.8,
+    }
+}
+
+fn fill(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Key => "#f4f4f4",
+        Kind::None => "#dddddd",
+        Kind::Trans => "#fbfbfb",
+        Kind::Layer => "#cfe3f7",
+        Kind::Mouse => "#d9f2d0",
+        Kind::Custom => "#f7e3c4",
+    }
+}
+
+fn text_color(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Trans => "#aaaaaa",
+        _ => "#000000",
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,52 @@
+use {
+    std::fs,
+    yuki_tools::{geometry, pcb},
+};
+
+fn switches() -> Vec<pcb::Switch> {
+    let src = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../yuki_pcb/yuki_pcb.kicad_pcb")).unwrap();
+    pcb::switches(&src).unwrap()
+}
+
+#[test]
+fn finds_every_switch() {
+    let switches = switches();
+
+    assert_eq!(switches.len(), 22);
+    assert!(switches.iter().all(|s| s.net == format!("/{}", s.reference.to_lowercase())));
+}
+
+#[test]
+fn matrix_matches_firmware() {
+    let half = pcb::matrix(&switches()).unwrap();
+
+    // the thumb row is wired to columns 2 to 5, the rest to all 6
+    let mut coords: Vec<_> = half.iter().map(|k| (k.row, k.col)).collect();
+    coords.sort();
+    let expected: Vec<_> = (0..4)
+        .flat_map(|r| (0..6).map(move |c| (r, c)))
+        .filter(|&(r, c)| r < 3 || c >= 2)
+        .collect();
+    assert_eq!(coords, expected);
+
+    let k = |row, col| *half.iter().find(|k| k.row == row && k.col == col).unwrap();
+    assert_eq!((k(0, 0).x, k(0, 0).y), (69.75, 70.0));
+    assert_eq!((k(2, 5).x, k(2, 5).y), (164.25, 94.25));
+    assert_eq!(k(3, 5).angle, 30.0);
+
+    // column stagger, the middle finger column is the highest
+    assert!((0..6).filter(|&c| c != 3).all(|c| k(0, c).y > k(0, 3).y));
+}
+
+#[test]
+fn right_half_is_mirrored() {
+    let keys = geometry::keyboard(&pcb::matrix(&switches()).unwrap());
+    let k = |row, col| *keys.iter().find(|k| k.row == row && k.col == col).unwrap();
+
+    assert_eq!(keys.len(), 44);
+    for (row, col) in [(0, 0), (1, 3), (3, 4)] {
+        assert_eq!(k(row, col).y, k(row, 11 - col).y);
+        assert_eq!(k(row, col).angle, -k(row, 11 - col).angle);
+    }
+    assert!(k(3, 5).x < k(3, 6).x);
+}
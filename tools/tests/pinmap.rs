@@ -0,0 +1,35 @@
+use {
+    std::fs,
+    yuki_tools::{pinmap, sch},
+};
+
+fn read(path: &str) -> String {
+    fs::read_to_string(format!("{}/../{}", env!("CARGO_MANIFEST_DIR"), path)).unwrap()
+}
+
+fn schematic() -> Vec<sch::Pin> {
+    sch::pins(&read("yuki_pcb/yuki_pcb.kicad_sch")).unwrap()
+}
+
+#[test]
+fn firmware_matches_schematic() {
+    let fw = pinmap::firmware(&read("src/main.rs")).unwrap();
+
+    assert_eq!(fw.matrix.len(), 4);
+    assert_eq!(fw.matrix[3][..2], [None, None]);
+    assert_eq!(pinmap::check(&fw, &schematic()).unwrap(), Vec::<String>::new());
+}
+
+#[test]
+fn reports_swapped_pins() {
+    let mut fw = pinmap::firmware(&read("src/main.rs")).unwrap();
+    fw.matrix[0].swap(0, 1);
+    fw.tx = None;
+
+    let errors = pinmap::check(&fw, &schematic()).unwrap();
+    assert_eq!(errors, [
+        "key (0, 0) K00: firmware uses PA8, schematic has PA9",
+        "key (0, 1) K01: firmware uses PA9, schematic has PA8",
+        "USART TX: firmware uses no pin, schematic has PB6",
+    ]);
+}
@@ -0,0 +1,138 @@
+use {
+    keyberon::{
+        action::Action,
+        key_code::KeyCode,
+        layout::Event,
+    },
+    crate::layout::{action_at, ActiveLayers, AUTO_SHIFT_EXCLUDED},
+};
+
+pub enum ASAction {
+    Toggle,
+    Increase,
+    Decrease,
+}
+
+const DEFAULT_TIMEOUT: u16 = 175;
+const TIMEOUT_STEP: u16 = 10;
+const MIN_TIMEOUT: u16 = 50;
+const MAX_TIMEOUT: u16 = 500;
+
+pub struct AutoShift {
+    pub enabled: bool,
+    pub timeout: u16,
+    // press that is held back until we know if its a tap or a long press
+    pending: Option<(Event, u16)>,
+    // key that was sent with shift after its timeout ran out
+    shifted: Option<(u8, u8)>,
+    // bit per (i, j) of the pressed keys that are not layer keys
+    held: u64,
+}
+
+impl Default for AutoShift {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AutoShift {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            timeout: DEFAULT_TIMEOUT,
+            pending: None,
+            shifted: None,
+            held: 0,
+        }
+    }
+
+    /// Passes `event` to `forward` unless its the press of a key that
+    /// should wait for the timeout, in which case it is released by `tick`.
+    pub fn event(&mut self, event: Event, layers: ActiveLayers, mut forward: impl FnMut(Event)) {
+        if let Some((pending, _)) = self.pending.take() {
+            forward(pending);
+            self.hold(pending, layers);
+
+            // any other event makes the pending key a normal tap,
+            // so hold-taps like CTRL_TAB still see the key in order
+            if !event.is_press() && event.coord() == pending.coord() {
+                self.release(event);
+                return forward(event);
+            }
+        }
+
+        if event.is_press() && self.enabled && self.held == 0 && is_shiftable(&event, layers) {
+            self.pending = Some((event, 0));
+            return;
+        }
+
+        match event.is_press() {
+            true => self.hold(event, layers),
+            false => self.release(event),
+        }
+
+        forward(event)
+    }
+
+    /// Returns the pending press once it has been held past the timeout.
+    pub fn tick(&mut self) -> Option<Event> {
+        let (event, ticks) = self.pending.as_mut()?;
+
+        *ticks += 1;
+        if *ticks < self.timeout {
+            return None;
+        }
+
+        let event = *event;
+        self.pending = None;
+        self.shifted = Some(event.coord());
+        self.held |= bit(event.coord());
+
+        Some(event)
+    }
+
+    /// Extra keycode to add to the report while a key is auto-shifted.
+    pub fn shift(&self) -> Option<KeyCode> {
+        self.shifted.map(|_| KeyCode::LShift)
+    }
+
+    pub fn handle_action(&mut self, action: &ASAction) {
+        match action {
+            ASAction::Toggle => self.enabled = !self.enabled,
+            ASAction::Increase => self.timeout = (self.timeout + TIMEOUT_STEP).min(MAX_TIMEOUT),
+            ASAction::Decrease => self.timeout = self.timeout.saturating_sub(TIMEOUT_STEP).max(MIN_TIMEOUT),
+        }
+    }
+
+    fn hold(&mut self, event: Event, layers: ActiveLayers) {
+        if !matches!(action_at(event.coord(), layers), Action::Layer(_)) {
+            self.held |= bit(event.coord());
+        }
+    }
+
+    fn release(&mut self, event: Event) {
+        self.held &= !bit(event.coord());
+
+        if self.shifted == Some(event.coord()) {
+            self.shifted = None;
+        }
+    }
+}
+
+fn bit((i, j): (u8, u8)) -> u64 {
+    1 << (i as u64 * 12 + j as u64)
+}
+
+// letters, numbers and the symbol keys from Minus to Slash
+fn is_shiftable(event: &Event, layers: ActiveLayers) -> bool {
+    match action_at(event.coord(), layers) {
+        Action::KeyCode(k) => {
+            let k = *k as u8;
+
+            ((KeyCode::A as u8..=KeyCode::Kb0 as u8).contains(&k) ||
+            (KeyCode::Minus as u8..=KeyCode::Slash as u8).contains(&k)) &&
+            !AUTO_SHIFT_EXCLUDED.iter().any(|e| *e as u8 == k)
+        },
+        _ => false
+    }
+}
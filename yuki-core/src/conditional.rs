@@ -0,0 +1,59 @@
+use {
+    keyberon::{action::Action, layout::Event},
+    heapless::Vec,
+    crate::layout::{action_at, ActiveLayers, CONDITIONAL_LAYERS},
+};
+
+pub struct ConditionalLayers {
+    // coords of the held layer keys and the layer they hold
+    held: Vec<((u8, u8), usize), 8>,
+    // index in CONDITIONAL_LAYERS of the condition that holds its virtual key
+    active: Option<usize>,
+}
+
+impl Default for ConditionalLayers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConditionalLayers {
+    pub fn new() -> Self {
+        Self { held: Vec::new(), active: None }
+    }
+
+    /// Forwards `event` and after it the press/release of a virtual key
+    /// when a condition from `CONDITIONAL_LAYERS` starts or stops holding.
+    pub fn event(&mut self, event: Event, layers: ActiveLayers, mut forward: impl FnMut(Event)) {
+        forward(event);
+
+        match event {
+            Event::Press(i, j) => {
+                if let Action::Layer(n) = action_at((i, j), layers) {
+                    let _ = self.held.push(((i, j), *n));
+                }
+            },
+            Event::Release(i, j) => self.held.retain(|(c, _)| *c != (i, j)),
+        }
+
+        let next = CONDITIONAL_LAYERS.iter().position(|c|
+            c.when.iter().all(|l| self.held.iter().any(|(_, h)| h == l))
+        );
+
+        if next == self.active {
+            return;
+        }
+
+        if let Some(prev) = self.active {
+            let (i, j) = CONDITIONAL_LAYERS[prev].coord;
+            forward(Event::Release(i, j));
+        }
+
+        if let Some(next) = next {
+            let (i, j) = CONDITIONAL_LAYERS[next].coord;
+            forward(Event::Press(i, j));
+        }
+
+        self.active = next;
+    }
+}
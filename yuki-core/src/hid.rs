@@ -0,0 +1,74 @@
+use {
+    heapless::Vec,
+    keyberon::key_code::KeyCode,
+    usbd_human_interface_device::{device::consumer::MultipleConsumerReport, page::Consumer},
+};
+
+/// Generic desktop system controls, the usage is the value.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SystemControl {
+    PowerDown = 0x81,
+    Sleep = 0x82,
+    WakeUp = 0x83,
+}
+
+/// Media keys go to the consumer device, most hosts ignore them in the
+/// keyboard report.
+pub fn consumer(key: KeyCode) -> Option<Consumer> {
+    Some(match key {
+        KeyCode::MediaPlayPause => Consumer::PlayPause,
+        KeyCode::MediaNextSong => Consumer::ScanNextTrack,
+        KeyCode::MediaPreviousSong => Consumer::ScanPreviousTrack,
+        KeyCode::MediaStop | KeyCode::MediaStopCD => Consumer::Stop,
+        KeyCode::MediaEjectCD => Consumer::Eject,
+        KeyCode::Mute | KeyCode::MediaMute => Consumer::Mute,
+        KeyCode::VolUp | KeyCode::MediaVolUp => Consumer::VolumeIncrement,
+        KeyCode::VolDown | KeyCode::MediaVolDown => Consumer::VolumeDecrement,
+        _ => return None,
+    })
+}
+
+pub fn system(key: KeyCode) -> Option<SystemControl> {
+    match key {
+        KeyCode::Power => Some(SystemControl::PowerDown),
+        KeyCode::MediaSleep => Some(SystemControl::Sleep),
+        _ => None,
+    }
+}
+
+/// What stays in the keyboard report.
+pub fn is_keyboard(key: KeyCode) -> bool {
+    consumer(key).is_none() && system(key).is_none()
+}
+
+/// Keys besides the modifiers in a boot keyboard report.
+pub const BOOT_KEYS: usize = 6;
+
+/// `keys` as a boot keyboard would send them: the modifiers and up to
+/// `BOOT_KEYS` others, with more the others are all the rollover error.
+pub fn six_kro(keys: impl Iterator<Item = KeyCode>) -> Vec<KeyCode, 16> {
+    let (mut mods, mut others): (Vec<KeyCode, 8>, Vec<KeyCode, 8>) = (Vec::new(), Vec::new());
+    let mut rollover = false;
+    for k in keys {
+        let held = match k.is_modifier() {
+            true => mods.push(k),
+            false => others.push(k),
+        };
+        rollover |= held.is_err() || others.len() > BOOT_KEYS;
+    }
+
+    if rollover {
+        others.clear();
+        others.extend([KeyCode::ErrorRollOver; BOOT_KEYS]);
+    }
+    mods.into_iter().chain(others).collect()
+}
+
+/// Up to four media keys at once, the rest are dropped.
+pub fn consumer_report(keys: impl Iterator<Item = KeyCode>) -> MultipleConsumerReport {
+    let mut codes = [Consumer::Unassigned; 4];
+    for (c, k) in codes.iter_mut().zip(keys.filter_map(consumer)) {
+        *c = k;
+    }
+    MultipleConsumerReport { codes }
+}
@@ -0,0 +1,138 @@
+use {
+    keyberon::{action::Action, layout::Event},
+    heapless::{String, Vec},
+    ufmt::uwrite,
+    crate::layout::{action_at, ActiveLayers, HOLD_TAP_TERMS, HOLD_TAP_TIMEOUT_COORD},
+};
+
+pub enum HTAction {
+    /// Cycles between adjusting all the hold-taps and each one of them.
+    Select,
+    Increase,
+    Decrease,
+}
+
+/// Terms that fit in the settings record.
+pub const MAX_TERMS: usize = 4;
+const _: () = assert!(HOLD_TAP_TERMS.len() <= MAX_TERMS);
+
+const TERM_STEP: u16 = 10;
+const MIN_TERM: u16 = 50;
+const MAX_TERM: u16 = 500;
+
+// how long the OLED shows what was changed
+const SHOW_TICKS: u16 = 2000;
+
+enum Shown {
+    Selected,
+    Term,
+}
+
+pub struct HoldTaps {
+    /// Term in ms of each of `HOLD_TAP_TERMS`.
+    pub terms: [u16; MAX_TERMS],
+    // None adjusts all of them to the same term
+    selected: Option<usize>,
+    // pressed hold-taps, index in HOLD_TAP_TERMS and ticks held,
+    // None once the term is over
+    held: Vec<((u8, u8), usize, Option<u16>), 4>,
+    shown: Option<(Shown, u16)>,
+}
+
+impl HoldTaps {
+    /// `saved` terms out of range are replaced by the default ones.
+    pub fn new(saved: [u16; MAX_TERMS]) -> Self {
+        let mut terms = [0; MAX_TERMS];
+        for (i, t) in HOLD_TAP_TERMS.iter().enumerate() {
+            terms[i] = match saved[i] {
+                s @ MIN_TERM..=MAX_TERM => s,
+                _ => t.term,
+            };
+        }
+
+        Self { terms, selected: None, held: Vec::new(), shown: None }
+    }
+
+    /// Notes the press and release of the hold-taps, `event` is the one
+    /// that goes to the layout.
+    pub fn event(&mut self, event: Event, layers: ActiveLayers) {
+        let coord = event.coord();
+
+        match event {
+            Event::Press(..) => {
+                // any press resolves the waiting hold-tap, only the last
+                // pressed one can still wait for its term, or the virtual
+                // key of an earlier term would resolve it too soon
+                self.held.iter_mut().for_each(|(_, _, ticks)| *ticks = None);
+
+                let Some(i) = HOLD_TAP_TERMS.iter().position(|t| t.coord == coord) else { return };
+                if matches!(action_at(coord, layers), Action::HoldTap(_)) {
+                    let _ = self.held.push((coord, i, Some(0)));
+                }
+            },
+            Event::Release(..) => self.held.retain(|(c, _, _)| *c != coord),
+        }
+    }
+
+    /// Returns the press and release of the virtual key that makes the
+    /// layout resolve a waiting hold-tap once its term is over.
+    pub fn tick(&mut self) -> Option<[Event; 2]> {
+        self.shown = self.shown.take().filter(|(_, t)| *t > 1).map(|(s, t)| (s, t - 1));
+
+        let mut over = false;
+        for (_, i, ticks) in self.held.iter_mut() {
+            if let Some(t) = ticks {
+                *t += 1;
+                if *t >= self.terms[*i] {
+                    *ticks = None;
+                    over = true;
+                }
+            }
+        }
+
+        let (i, j) = HOLD_TAP_TIMEOUT_COORD;
+        over.then_some([Event::Press(i, j), Event::Release(i, j)])
+    }
+
+    pub fn handle_action(&mut self, action: &HTAction) {
+        match action {
+            HTAction::Select => {
+                self.selected = match self.selected {
+                    None => Some(0),
+                    Some(i) if i + 1 < HOLD_TAP_TERMS.len() => Some(i + 1),
+                    Some(_) => None,
+                };
+                self.shown = Some((Shown::Selected, SHOW_TICKS));
+                return;
+            },
+            HTAction::Increase => self.set(self.term() + TERM_STEP),
+            HTAction::Decrease => self.set(self.term().saturating_sub(TERM_STEP)),
+        }
+        self.shown = Some((Shown::Term, SHOW_TICKS));
+    }
+
+    /// The term being adjusted, the first one when adjusting all.
+    pub fn term(&self) -> u16 {
+        self.terms[self.selected.unwrap_or(0)]
+    }
+
+    fn set(&mut self, term: u16) {
+        let term = term.clamp(MIN_TERM, MAX_TERM);
+        match self.selected {
+            Some(i) => self.terms[i] = term,
+            None => self.terms[..HOLD_TAP_TERMS.len()].fill(term),
+        }
+    }
+
+    /// What to show on the OLED for a while after a change.
+    pub fn label(&self) -> Option<String<8>> {
+        let mut txt = String::new();
+        match self.shown.as_ref()? {
+            (Shown::Selected, _) => {
+                let _ = txt.push_str(self.selected.map_or("All", |i| HOLD_TAP_TERMS[i].name));
+            },
+            (Shown::Term, _) => { let _ = uwrite!(&mut txt, "{}", self.term()); },
+        }
+        Some(txt)
+    }
+}
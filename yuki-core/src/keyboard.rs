@@ -0,0 +1,251 @@
+use {
+    core::ops::Range,
+    heapless::String,
+    usbd_human_interface_device::device::consumer::MultipleConsumerReport,
+    keyberon::{
+        key_code::{KbHidReport, KeyCode},
+        layout::{CustomEvent, Event, Layout},
+    },
+    crate::{
+        autoshift::AutoShift,
+        conditional::ConditionalLayers,
+        hid::{self, SystemControl},
+        holdtap::{HoldTaps, MAX_TERMS},
+        layout::{ActiveLayers, CustomAction, LAYERS, LAYER_SPEEDS},
+        mouse::Mouse,
+        os::{OsDetect, Shortcuts},
+        overrides::{self, Keys},
+        pointing::Pointing,
+        repeat::Repeat,
+        restart::{HoldToConfirm, Restart},
+        suspend::Suspend,
+        swap::SwapHands,
+    },
+};
+
+pub const NUM_COLS: usize = 12;
+pub const NUM_ROWS: usize = 4;
+pub const NUM_LAYERS: usize = LAYERS.len();
+
+const USE_RIGHT_USB_INIT: bool = true;
+const NKRO_INIT: bool = true;
+
+/// The state of both halves, fed with the matrix events of both and ticked
+/// every ms. The hardware side only moves the events, reports and link
+/// messages around.
+pub struct Keyboard {
+    pub layout: Layout<NUM_COLS, NUM_ROWS, NUM_LAYERS, CustomAction>,
+    pub mouse: Mouse,
+    pub pointing: Pointing,
+    pub autoshift: AutoShift,
+    pub conditional: ConditionalLayers,
+    pub swap: SwapHands,
+    pub hold_taps: HoldTaps,
+    pub os: OsDetect,
+    pub repeat: Repeat,
+    pub shortcuts: Shortcuts,
+    pub use_right_usb: bool,
+    /// Every key in the report, or 6KRO like a boot keyboard for hosts
+    /// that struggle with NKRO in report protocol.
+    pub nkro: bool,
+    pub confirm: HoldToConfirm,
+    /// Set once a restart key was held long enough, the hardware side
+    /// does the restart.
+    pub restart: Option<Restart>,
+    pub suspend: Suspend,
+    /// Keys of the last tick, modifiers included.
+    pub keys: Keys,
+    // keyberon doesn't tell, it is needed to resolve the `t` keys
+    default_layer: usize,
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Keyboard {
+    pub fn new() -> Self {
+        Self {
+            layout: Layout::new(&LAYERS),
+            mouse: Mouse::new(),
+            pointing: Pointing::new(),
+            autoshift: AutoShift::new(),
+            conditional: ConditionalLayers::new(),
+            swap: SwapHands::new(),
+            hold_taps: HoldTaps::new([u16::MAX; MAX_TERMS]),
+            os: OsDetect::new(),
+            repeat: Repeat::new(),
+            shortcuts: Shortcuts::new(),
+            use_right_usb: USE_RIGHT_USB_INIT,
+            nkro: NKRO_INIT,
+            confirm: HoldToConfirm::new(),
+            restart: None,
+            suspend: Suspend::new(),
+            keys: Keys::new(),
+            default_layer: 0,
+        }
+    }
+
+    pub fn press(&mut self, i: u8, j: u8) {
+        self.event(Event::Press(i, j))
+    }
+
+    pub fn release(&mut self, i: u8, j: u8) {
+        self.event(Event::Release(i, j))
+    }
+
+    /// An event from either half, in the coords of the whole keyboard.
+    pub fn event(&mut self, event: Event) {
+        if event.is_press() {
+            self.suspend.press();
+        }
+
+        if self.mouse.warp.event(event) {
+            return;
+        }
+
+        let layers = self.layers();
+        let event = self.swap.event(event, layers);
+
+        let (layout, conditional, hold_taps) = (&mut self.layout, &mut self.conditional, &mut self.hold_taps);
+        self.autoshift.event(event, layers, |e| conditional.event(e, layers, |e| {
+            hold_taps.event(e, layers);
+            layout.event(e)
+        }))
+    }
+
+    /// Releases every key of `cols`, for when the events of a half were lost.
+    pub fn release_cols(&mut self, cols: Range<u8>) {
+        for i in 0..NUM_ROWS as u8 {
+            cols.clone().for_each(|j| self.release(i, j));
+        }
+    }
+
+    /// Counts from the pointing sensor of either half.
+    pub fn motion(&mut self, motion: (i16, i16)) {
+        if let Some(e) = self.pointing.motion(motion, &mut self.mouse) {
+            self.hold_taps.event(e, self.layers());
+            self.layout.event(e)
+        }
+    }
+
+    /// Returns false for layers that don't exist.
+    pub fn set_default_layer(&mut self, layer: u8) -> bool {
+        if layer as usize >= NUM_LAYERS {
+            return false;
+        }
+
+        self.layout.set_default_layer(layer as usize);
+        self.default_layer = layer as usize;
+        true
+    }
+
+    fn layers(&self) -> ActiveLayers {
+        ActiveLayers { current: self.layout.current_layer(), default: self.default_layer }
+    }
+
+    /// One ms, returns the custom event of the layout so the hardware
+    /// side can act on it too (default layer changes go to flash and
+    /// the other half).
+    pub fn tick(&mut self) -> CustomEvent<CustomAction> {
+        if let Some(e) = self.autoshift.tick() {
+            self.hold_taps.event(e, self.layers());
+            self.layout.event(e)
+        }
+
+        if let Some(events) = self.hold_taps.tick() {
+            events.into_iter().for_each(|e| self.layout.event(e))
+        }
+
+        self.mouse.layer_speed = LAYER_SPEEDS[self.layout.current_layer()];
+        self.mouse.mouse_tick();
+        if let Some(e) = self.pointing.tick(&self.mouse) {
+            self.hold_taps.event(e, self.layers());
+            self.layout.event(e)
+        }
+        if let Some(r) = self.confirm.tick() {
+            self.restart = Some(r);
+        }
+
+        let event = self.layout.tick();
+        match event {
+            CustomEvent::NoEvent => (),
+            CustomEvent::Press(CustomAction::USB) => self.use_right_usb = !self.use_right_usb,
+            CustomEvent::Press(CustomAction::Nkro) => self.nkro = !self.nkro,
+            CustomEvent::Press(CustomAction::M(maction)) => {
+                self.mouse.handle_mouse_btn(maction, true);
+                self.pointing.keep_auto_mouse();
+            },
+            CustomEvent::Release(CustomAction::M(maction)) => self.mouse.handle_mouse_btn(maction, false),
+            CustomEvent::Press(CustomAction::Pointing(paction)) => self.pointing.handle_action(paction, true),
+            CustomEvent::Release(CustomAction::Pointing(paction)) => self.pointing.handle_action(paction, false),
+            CustomEvent::Press(CustomAction::AS(asaction)) => self.autoshift.handle_action(asaction),
+            CustomEvent::Press(CustomAction::HT(htaction)) => self.hold_taps.handle_action(htaction),
+            CustomEvent::Press(CustomAction::Repeat) => self.repeat.press(false),
+            CustomEvent::Press(CustomAction::AltRepeat) => self.repeat.press(true),
+            CustomEvent::Release(CustomAction::Repeat | CustomAction::AltRepeat) => self.repeat.release(),
+            CustomEvent::Press(CustomAction::Swap(saction)) => self.swap.handle_action(saction, true),
+            CustomEvent::Release(CustomAction::Swap(saction)) => self.swap.handle_action(saction, false),
+            CustomEvent::Press(CustomAction::Shortcut(sc)) => self.shortcuts.press(sc),
+            CustomEvent::Release(CustomAction::Shortcut(sc)) => self.shortcuts.release(sc),
+            CustomEvent::Press(CustomAction::Os) => self.os.cycle_manual(),
+            CustomEvent::Press(CustomAction::Bootloader) => self.confirm.press(Restart::Bootloader),
+            CustomEvent::Release(CustomAction::Bootloader) => self.confirm.release(Restart::Bootloader),
+            CustomEvent::Press(CustomAction::Reset) => self.confirm.press(Restart::Reset),
+            CustomEvent::Release(CustomAction::Reset) => self.confirm.release(Restart::Reset),
+            CustomEvent::Press(CustomAction::DefaultLayer(layer)) => { self.set_default_layer(*layer); },
+            _ => ()
+        }
+
+        let shift = self.autoshift.shift();
+        let shortcuts = self.shortcuts.keys(self.os.host());
+        self.keys = overrides::collect_keys(self.layout.keycodes().chain(shift).chain(shortcuts));
+        overrides::apply(&mut self.keys, self.layout.current_layer());
+        self.repeat.update(&mut self.keys);
+
+        event
+    }
+
+    /// Runs `n` ticks, the custom events are dropped.
+    pub fn ticks(&mut self, n: usize) {
+        (0..n).for_each(|_| { self.tick(); })
+    }
+
+    /// The keys of the keyboard report, the media and system ones go
+    /// to their own devices.
+    pub fn keyboard_keys(&self) -> impl Iterator<Item = KeyCode> {
+        let keys = self.keys.iter().copied().filter(|k| hid::is_keyboard(*k));
+        let keys = match self.nkro {
+            true => overrides::collect_keys(keys),
+            false => overrides::collect_keys(hid::six_kro(keys)),
+        };
+        keys.into_iter()
+    }
+
+    pub fn report(&self) -> KbHidReport {
+        self.keyboard_keys().collect()
+    }
+
+    pub fn consumer_report(&self) -> MultipleConsumerReport {
+        hid::consumer_report(self.keys.iter().copied())
+    }
+
+    /// The first system key held, the device only takes one.
+    pub fn system_control(&self) -> Option<SystemControl> {
+        self.keys.iter().find_map(|k| hid::system(*k))
+    }
+
+    pub fn pressed(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// What the OLED shows instead of the host OS for now, the speed
+    /// mode stays while it is on.
+    pub fn info(&self) -> Option<String<8>> {
+        self.mouse.warp.label()
+            .or_else(|| self.hold_taps.label())
+            .or_else(|| self.mouse.speed_label())
+    }
+}
@@ -0,0 +1,330 @@
+use keyberon::action::{k, m, Action::*, HoldTapAction, HoldTapConfig};
+use keyberon::key_code::KeyCode::*;
+use keyberon::key_code::KeyCode;
+use crate::mouse::{MAction, Dir, Motion, Profile, Speed, SpeedModes, Wheel};
+use crate::autoshift::ASAction;
+use crate::holdtap::HTAction;
+use crate::swap::SwapAction;
+use crate::overrides::{KeyOverride, SHIFT};
+use crate::os::Shortcut;
+use crate::pointing::{PAction, PointingConfig};
+ 
+pub enum CustomAction {
+    M(MAction),
+    Pointing(PAction),
+    AS(ASAction),
+    HT(HTAction),
+    Swap(SwapAction),
+    Repeat,
+    AltRepeat,
+    Shortcut(&'static Shortcut),
+    Os,
+    DefaultLayer(u8),
+    USB,
+    /// Switches the keyboard report between NKRO and 6KRO.
+    Nkro,
+    /// Held to confirm, also puts the other half in the bootloader.
+    Bootloader,
+    /// Held to confirm.
+    Reset,
+}
+
+// keys that are never auto-shifted
+pub const AUTO_SHIFT_EXCLUDED: &[KeyCode] = &[Grave, Slash, Minus];
+
+type Action = keyberon::action::Action<CustomAction>;
+
+// default layer changes are saved to flash and sent to the other half
+const LAYER0: Action = Custom(CustomAction::DefaultLayer(0));
+const LAYER3: Action = Custom(CustomAction::DefaultLayer(3));
+const LAYER4: Action = Custom(CustomAction::DefaultLayer(4));
+
+// shortcuts resolve to the ctrl or cmd variant for the detected host
+const REDO_SC: Shortcut = Shortcut { pc: &[LCtrl, Y], mac: &[LGui, LShift, Z] };
+const UNDO_SC: Shortcut = Shortcut { pc: &[LCtrl, Z], mac: &[LGui, Z] };
+const COPY_SC: Shortcut = Shortcut { pc: &[LCtrl, C], mac: &[LGui, C] };
+const PASTE_SC: Shortcut = Shortcut { pc: &[LCtrl, V], mac: &[LGui, V] };
+const CUT_SC: Shortcut = Shortcut { pc: &[LCtrl, X], mac: &[LGui, X] };
+const CTRL_BS_SC: Shortcut = Shortcut { pc: &[LCtrl, BSpace], mac: &[LAlt, BSpace] };
+
+const REDO: Action = Custom(CustomAction::Shortcut(&REDO_SC));
+const UNDO: Action = Custom(CustomAction::Shortcut(&UNDO_SC));
+const COPY: Action = Custom(CustomAction::Shortcut(&COPY_SC));
+const PASTE: Action = Custom(CustomAction::Shortcut(&PASTE_SC));
+const CUT: Action = Custom(CustomAction::Shortcut(&CUT_SC));
+const CTRL_BS: Action = Custom(CustomAction::Shortcut(&CTRL_BS_SC));
+
+const MOD_F4: Action = m(&[LGui, F4].as_slice());
+const MOD_F5: Action = m(&[LGui, F5].as_slice());
+
+// the hold-taps are resolved by their term in HOLD_TAP_TERMS, which can be
+// changed at runtime, so keyberon's own timeout is never reached.
+// `tap_hold_interval` is still keyberon's: pressing the key again that soon
+// after a tap repeats the tap, it doesn't follow the adjusted term
+const RUNTIME_TERM: u16 = u16::MAX;
+
+const CTRL_TAB: Action = HoldTap(&HoldTapAction {
+    timeout: RUNTIME_TERM,
+    tap_hold_interval: 180,
+    config: HoldTapConfig::HoldOnOtherKeyPress,
+    hold: k(LCtrl),
+    tap: k(Tab),
+});
+
+const ALT_ENTER: Action = HoldTap(&HoldTapAction {
+    timeout: RUNTIME_TERM,
+    tap_hold_interval: 200,
+    config: HoldTapConfig::HoldOnOtherKeyPress,
+    hold: k(LAlt),
+    tap: k(Enter),
+});
+
+const USB: Action = Custom(CustomAction::USB);
+const NKRO: Action = Custom(CustomAction::Nkro);
+const BOOT: Action = Custom(CustomAction::Bootloader);
+const RESET: Action = Custom(CustomAction::Reset);
+const OS: Action = Custom(CustomAction::Os);
+
+// on layer 1 they replace two `t` keys: Escape, which stays on layers 0
+// and 3, and the (3) of the right thumb, the mouse layer is still
+// there by holding (2) too
+const REP: Action = Custom(CustomAction::Repeat);
+const AREP: Action = Custom(CustomAction::AltRepeat);
+
+const SWM: Action = Custom(CustomAction::Swap(SwapAction::Momentary));
+const SWT: Action = Custom(CustomAction::Swap(SwapAction::Toggle));
+const SWO: Action = Custom(CustomAction::Swap(SwapAction::OneShot));
+
+const HTS: Action = Custom(CustomAction::HT(HTAction::Select));
+const HTI: Action = Custom(CustomAction::HT(HTAction::Increase));
+const HTD: Action = Custom(CustomAction::HT(HTAction::Decrease));
+
+const AST: Action = Custom(CustomAction::AS(ASAction::Toggle));
+const ASI: Action = Custom(CustomAction::AS(ASAction::Increase));
+const ASD: Action = Custom(CustomAction::AS(ASAction::Decrease));
+
+/// Speed of the mouse keys, see `Profile` for the others.
+pub const MOUSE_MOTION: Motion = Motion {
+    profile: Profile::Linear,
+    start_speed: 300,
+    max_speed: 2000,
+    time_to_max: 500,
+};
+
+/// Repeat of the scroll keys.
+pub const MOUSE_WHEEL: Wheel = Wheel {
+    delay: 300,
+    interval: 100,
+    min_interval: 20,
+    accel: 10,
+};
+
+/// Precision and turbo modes of the mouse keys and the pointing sensors.
+pub const MOUSE_SPEEDS: SpeedModes = SpeedModes {
+    precision: 25,
+    turbo: 250,
+};
+
+macro_rules! ma {
+    ($name:ident, $action:expr) => {
+        const $name: Action = Custom(CustomAction::M($action));
+    };
+}
+
+ma!(TA, MAction::ToggleActive);
+ma!(TS, MAction::Speedup);
+ma!(PREC, MAction::Precision);
+ma!(PRET, MAction::ToggleSpeed(Speed::Precision));
+ma!(TURT, MAction::ToggleSpeed(Speed::Turbo));
+
+ma!(M1, MAction::Left);
+ma!(M2, MAction::Right);
+ma!(M3, MAction::Middle);
+ma!(M4, MAction::Back);
+ma!(M5, MAction::Forward);
+ma!(DRAG, MAction::DragLock);
+ma!(WARP3, MAction::Warp(3));
+ma!(WARP4, MAction::Warp(4));
+
+const SNIP: Action = Custom(CustomAction::Pointing(PAction::Sniping));
+const DRGS: Action = Custom(CustomAction::Pointing(PAction::DragScroll));
+
+/// The trackball or trackpad of one half, see `Sensor`.
+pub const POINTING: PointingConfig = PointingConfig {
+    sensor_cpi: 800,
+    cpi: 800,
+    sniping_cpi: 200,
+    drag_scroll_counts: 40,
+    auto_mouse_timeout: 700,
+};
+
+ma!(UP, MAction::Move(Dir::Up));
+ma!(DOWN, MAction::Move(Dir::Down));
+ma!(LEFT, MAction::Move(Dir::Left));
+ma!(RIGHT, MAction::Move(Dir::Right));
+
+ma!(SCROLLU, MAction::Scroll(Dir::Up));
+ma!(SCROLLD, MAction::Scroll(Dir::Down));
+ma!(SCROLLL, MAction::Scroll(Dir::Left));
+ma!(SCROLLR, MAction::Scroll(Dir::Right));
+
+macro_rules! s {
+    ($k:ident) => {
+        m(&[LShift, $k].as_slice())
+    };
+}
+
+
+pub const LAYERS: keyberon::layout::Layers<12, 4, 6, CustomAction> = keyberon::layout::layout! {
+    // DVORAK
+    {
+        [ Grave     Quote     Comma     Dot       P              Y                        F           G         C           R            L         Slash             ]
+        [ Escape    A         O         E         U              I                        D           H         T           N            S         Minus             ]
+        [ LShift    SColon    Q         J         K              X                        B           M         W           V            Z         Delete            ]
+        [ n         n         (2)       LGui      Space          {ALT_ENTER}              LShift      (1)       {CTRL_TAB}  (3)          (3)       n                 ]
+    }
+    // NUMBERS & SYMBOLS
+    {
+        [ Tab       {s!(Kb1)} {s!(Kb2)} {s!(Kb3)} {s!(Kb4)}      {s!(Kb5)}                {s!(Kb6)}   {s!(Kb7)} {s!(Kb8)}   {s!(Kb9)}    {s!(Kb0)} MediaNextSong     ]
+        [ {AREP}    {k(Kb1)}  {k(Kb2)}  {k(Kb3)}  {k(Kb4)}       {k(Kb5)}                 {k(Kb6)}    {k(Kb7)}  {k(Kb8)}    {k(Kb9)}     {k(Kb0)}  MediaPlayPause    ]
+        [ LShift    {CTRL_BS} LBracket  RBracket  {s!(LBracket)} {s!(RBracket)}           {s!(Equal)} Equal     Bslash      {s!(Bslash)} BSpace    MediaPreviousSong ]
+        [ (3)       n         t         LGui      Space          {ALT_ENTER}              LShift      (1)       {CTRL_TAB}  {REP}        (3)       n                 ]
+    }
+    // MACROS
+    {
+        [ {LAYER3}  F1        F2        F3        F4             F5                       F6          F7        F8          F9           F10       {LAYER4}          ]
+        [ CapsLock  {LAYER4}  {AST}     Insert    Pause          PScreen                  Home        {ASI}     Up          F11          F12       PgUp              ]
+        [ LShift    {UNDO}    {CUT}     {COPY}    {PASTE}        {REDO}                   End         Left      Down        Right        {ASD}     PgDown            ]
+        [ (3)       n         t         LGui      {LAYER0}       {ALT_ENTER}              LShift      (1)       {CTRL_TAB}  {USB}        (3)       (5)               ]
+    }
+    // MOUSE
+    {
+        [ {TA}      Mute      VolDown   VolUp     {MOD_F4}       {MOD_F5}                 {DRGS}      {HTS}     {HTD}       {HTI}        {SCROLLR} {PREC}            ]
+        [ Escape    {SWT}     {M2}      {M3}      {M1}           {TS}                     {WARP3}     {SCROLLL} {UP}        {SNIP}       {M5}      {BOOT}            ]
+        [ LShift    {SWM}     {SWO}     {SCROLLD} {SCROLLU}      {DRAG}                   {WARP4}     {LEFT}    {DOWN}      {RIGHT}      {M4}      {RESET}           ]
+        [ n         n         (2)       LGui      Space          {ALT_ENTER}              LShift      (1)       {CTRL_TAB}  {LAYER0}     n         (5)               ]
+    }
+    // QWERTY
+    {
+        [ Tab       Q         W         E         R              T                        Y           U         I           O            P         n                 ]
+        [ Escape    A         S         D         F              G                        H           J         K           L            SColon    Quote             ]
+        [ LShift    Z         X         C         V              B                        N           M         Comma       Dot          Slash     Delete            ]
+        [ n         n         (2)       LCtrl     Space          {ALT_ENTER}              LShift     (1)        {CTRL_TAB}  {LAYER0}     (3)       n                 ]
+    }
+    // SETTINGS
+    {
+        [ n         n         n         n         n              n                        n           n         n           n            n         {OS}              ]
+        [ {PRET}    {TURT}    n         n         {NKRO}         n                        n           n         n           n            n         n                 ]
+        [ n         n         n         n         n              n                        n           n         n           n            n         n                 ]
+        [ n         n         t         t         t              t                        t           t         t           t            n         n                 ]
+    }
+};
+
+pub const KEY_OVERRIDES: &[KeyOverride] = &[
+    // shift + backspace = delete
+    KeyOverride { mods: SHIFT, trigger: BSpace, replacement: &[Delete], layers: &[1] },
+];
+
+/// Counterparts sent by the alt repeat key, both ways.
+pub const ALT_REPEAT: &[(&[KeyCode], &[KeyCode])] = &[
+    (&[Up], &[Down]),
+    (&[Left], &[Right]),
+    (&[PgUp], &[PgDown]),
+    (&[Home], &[End]),
+    (UNDO_SC.pc, REDO_SC.pc),
+    (UNDO_SC.mac, REDO_SC.mac),
+];
+
+/// Position each key is read from while hands are swapped.
+/// The thumb row is not mirrored so layer and modifier keys stay put.
+pub const SWAP_HANDS: [[(u8, u8); 12]; 4] = [
+    [(0, 11), (0, 10), (0, 9), (0, 8), (0, 7), (0, 6), (0, 5), (0, 4), (0, 3), (0, 2), (0, 1), (0, 0)],
+    [(1, 11), (1, 10), (1, 9), (1, 8), (1, 7), (1, 6), (1, 5), (1, 4), (1, 3), (1, 2), (1, 1), (1, 0)],
+    [(2, 11), (2, 10), (2, 9), (2, 8), (2, 7), (2, 6), (2, 5), (2, 4), (2, 3), (2, 2), (2, 1), (2, 0)],
+    [(3, 0),  (3, 1),  (3, 2), (3, 3), (3, 4), (3, 5), (3, 6), (3, 7), (3, 8), (3, 9), (3, 10), (3, 11)],
+];
+
+/// Speed mode of the mouse on each layer, while no precision or turbo
+/// key is held or toggled.
+pub const LAYER_SPEEDS: [Speed; LAYERS.len()] = [
+    Speed::Normal,
+    Speed::Normal,
+    Speed::Normal,
+    Speed::Normal,
+    Speed::Normal,
+    Speed::Normal,
+];
+
+/// Keys of the warp grid cells by (row, col) of the cell, on the left
+/// hand so the right one clicks.
+pub const WARP_GRID_3: [[(u8, u8); 3]; 3] = [
+    [(0, 2), (0, 3), (0, 4)],
+    [(1, 2), (1, 3), (1, 4)],
+    [(2, 2), (2, 3), (2, 4)],
+];
+pub const WARP_GRID_4: [[(u8, u8); 4]; 4] = [
+    [(0, 1), (0, 2), (0, 3), (0, 4)],
+    [(1, 1), (1, 2), (1, 3), (1, 4)],
+    [(2, 1), (2, 2), (2, 3), (2, 4)],
+    [(3, 2), (3, 3), (3, 4), (3, 5)],
+];
+/// While warping, clicks where the pointer is and stops.
+pub const WARP_CLICK: (u8, u8) = (1, 7);
+/// While warping, back to the previous cell.
+pub const WARP_BACK: (u8, u8) = (1, 8);
+
+/// A hold-tap whose term can be changed from the keyboard, by the
+/// coord it has in every layer.
+pub struct HoldTapTerm {
+    pub coord: (u8, u8),
+    /// Shown on the OLED while it is selected, 3 characters fit.
+    pub name: &'static str,
+    /// Default term in ms.
+    pub term: u16,
+}
+
+pub const HOLD_TAP_TERMS: &[HoldTapTerm] = &[
+    HoldTapTerm { coord: (3, 8), name: "C-T", term: 180 },
+    HoldTapTerm { coord: (3, 5), name: "A-E", term: 200 },
+];
+
+/// Virtual key pressed to resolve a waiting hold-tap once its term is
+/// over, unused by the matrix and `n` in every layer.
+pub const HOLD_TAP_TIMEOUT_COORD: (u8, u8) = (3, 1);
+
+/// Layer `then` is active while all the `when` layers are held.
+/// It is held by a virtual key at `coord`, so that coord has to be
+/// `(then)` in the `when` layers and unused by the matrix.
+pub struct ConditionalLayer {
+    pub when: &'static [usize],
+    pub then: usize,
+    pub coord: (u8, u8),
+}
+
+pub const CONDITIONAL_LAYERS: &[ConditionalLayer] = &[
+    // holding both thumb layers gives the mouse layer
+    ConditionalLayer { when: &[1, 2], then: 3, coord: (3, 0) },
+    // holding the mouse layer and then (2) gives the settings layer
+    ConditionalLayer { when: &[2, 3], then: 5, coord: (3, 11) },
+];
+
+/// Virtual key pressed while the sensor moves, it holds the mouse layer
+/// so it is `(3)` on the other layers and unused by the matrix.
+pub const AUTO_MOUSE_COORD: (u8, u8) = (3, 10);
+
+/// The layer the keys are read from and the default one, where its
+/// transparent keys are read from like keyberon does.
+#[derive(Clone, Copy)]
+pub struct ActiveLayers {
+    pub current: usize,
+    pub default: usize,
+}
+
+/// Action at `coord` on the current layer, transparent keys fall back to
+/// the default layer.
+pub fn action_at((i, j): (u8, u8), layers: ActiveLayers) -> &'static Action {
+    match &LAYERS[layers.current][i as usize][j as usize] {
+        Trans => &LAYERS[layers.default][i as usize][j as usize],
+        a => a,
+    }
+}
@@ -0,0 +1,25 @@
+//! The keyboard without the hardware: the keymap and everything acting on
+//! it, the split link protocol, the OLED screen and the settings records.
+//! The firmware in src/main.rs only wires these to the stm32 peripherals.
+
+#![no_std]
+
+pub mod autoshift;
+pub mod conditional;
+pub mod hid;
+pub mod holdtap;
+pub mod keyboard;
+pub mod layout;
+pub mod link;
+pub mod mouse;
+pub mod oled;
+pub mod os;
+pub mod overrides;
+pub mod pointing;
+pub mod repeat;
+pub mod reports;
+pub mod restart;
+pub mod settings;
+pub mod suspend;
+pub mod swap;
+pub mod warp;
@@ -0,0 +1,75 @@
+use keyberon::layout::Event;
+
+/// What the halves send each other over the TRRS serial link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Msg {
+    Event(Event),
+    DefaultLayer(u8),
+    /// Restart into the bootloader.
+    Bootloader,
+    /// The host suspended or resumed the bus.
+    Suspend(bool),
+    /// Sensor counts, see `take_motion`.
+    MotionX(i8),
+    MotionY(i8),
+}
+
+/// Most counts of one axis a motion message carries.
+pub const MAX_MOTION: i16 = 7;
+
+/// Takes what fits in a motion message from `pending`, the rest is sent
+/// over the next ticks.
+pub fn take_motion(pending: &mut i16) -> i8 {
+    let d = (*pending).clamp(-MAX_MOTION, MAX_MOTION);
+    *pending -= d;
+    d as i8
+}
+
+// im using one byte for the coords and the is_press status
+// the last three bits are the y
+// the second to last three are the x
+// the first bit is the is_press status
+//                                     press     5       2
+// so for example Event::Press(2, 5) == (1) 0 (1 0 1) (0 1 0)
+//
+// this only works with 8 or less columns or rows
+//
+// the second bit is never set by an event, so it marks the other messages,
+// their kind is in the next two bits and the value in the last four
+// so for example Msg::DefaultLayer(4) == 0 (1) (0 0) (0 1 0 0)
+// and Msg::Bootloader == 0 (1) (0 1) (0 0 0 0)
+// the suspend ones share its kind, Msg::Suspend(true) == 0 (1) (0 1) (0 0 0 1)
+// the motion ones have a signed value, Msg::MotionY(-2) == 0 (1) (1 1) (1 1 1 0)
+pub fn ser(msg: Msg) -> u8 {
+    match msg {
+        Msg::Event(e) => {
+            let (y, x) = e.coord();
+            y | x << 3 | (e.is_press() as u8) << 7
+        },
+        Msg::DefaultLayer(layer) => 64 | layer & 15,
+        Msg::Bootloader => 64 | 1 << 4,
+        Msg::Suspend(suspended) => 64 | 1 << 4 | if suspended { 1 } else { 2 },
+        Msg::MotionX(d) => 64 | 2 << 4 | d as u8 & 15,
+        Msg::MotionY(d) => 64 | 3 << 4 | d as u8 & 15,
+    }
+}
+
+pub fn de(n: u8) -> Result<Msg, ()> {
+    match (n & 128, n & 64) {
+        (128, 0) => Ok(Msg::Event(Event::Press(n & 7, (n & 56) >> 3))),
+        (0, 0) => Ok(Msg::Event(Event::Release(n & 7, (n & 56) >> 3))),
+        (0, 64) => match (n & 48) >> 4 {
+            0 => Ok(Msg::DefaultLayer(n & 15)),
+            1 => match n & 15 {
+                0 => Ok(Msg::Bootloader),
+                1 => Ok(Msg::Suspend(true)),
+                2 => Ok(Msg::Suspend(false)),
+                _ => Err(()),
+            },
+            // sign extended from the four bits
+            2 => Ok(Msg::MotionX(((n & 15) << 4) as i8 >> 4)),
+            _ => Ok(Msg::MotionY(((n & 15) << 4) as i8 >> 4)),
+        },
+        _ => Err(())
+    } 
+}
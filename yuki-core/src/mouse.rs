@@ -0,0 +1,385 @@
+use {
+    core::f32::consts::FRAC_1_SQRT_2,
+    heapless::String,
+    usbd_human_interface_device::device::mouse::WheelMouseReport,
+    crate::{
+        layout::{MOUSE_MOTION, MOUSE_SPEEDS, MOUSE_WHEEL},
+        warp::Warp,
+    },
+};
+
+pub enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+pub enum MAction {
+    ToggleActive,
+    Left,
+    Right,
+    Middle,
+    /// Buttons 4 and 5.
+    Back,
+    Forward,
+    /// Keeps the left button held until it is pressed again, or clicked.
+    DragLock,
+
+    Move(Dir),
+    /// Turbo mode while held.
+    Speedup,
+    /// Precision mode while held.
+    Precision,
+    /// Turns the mode on until it is toggled again, over the one of the
+    /// layer.
+    ToggleSpeed(Speed),
+
+    Scroll(Dir),
+
+    /// Starts or stops warping with a 3x3 or 4x4 grid.
+    Warp(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Profile {
+    /// Max speed right away.
+    Constant,
+    /// From the start speed to the max one over `time_to_max`.
+    Linear,
+    /// Stays slow for a while before speeding up, for both small and
+    /// large moves.
+    Kinetic,
+    /// Speeds up and slows down over `time_to_max`, so the cursor keeps
+    /// gliding for a bit after the keys are released.
+    Inertia,
+}
+
+/// How the cursor moves while the move keys are held, speeds in pixels
+/// per second.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Motion {
+    pub profile: Profile,
+    pub start_speed: u16,
+    pub max_speed: u16,
+    /// In ms.
+    pub time_to_max: u16,
+}
+
+/// Scales the whole of the motion and scroll, of the keys and the
+/// pointing sensors.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Speed {
+    Normal,
+    Precision,
+    Turbo,
+}
+
+/// Percent of the normal speed in each mode.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpeedModes {
+    pub precision: u16,
+    pub turbo: u16,
+}
+
+/// Repeat of the scroll keys, times in ms. The first repeat comes after
+/// `delay`, then one every `interval`, shortened by `accel` percent with
+/// every notch down to `min_interval`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Wheel {
+    pub delay: u16,
+    pub interval: u16,
+    pub min_interval: u16,
+    pub accel: u8,
+}
+
+/// Mouse state driven by the `MAction` keys, the usb class that sends
+/// `report` (and the absolute one of `warp`) lives with the other usb
+/// classes in main.rs.
+pub struct Mouse {
+    pub report: WheelMouseReport,
+    pub warp: Warp,
+    pub active: bool,
+    // the buttons held by their keys
+    buttons: u8,
+    drag_lock: bool,
+    pub motion: Motion,
+    // up, down, left, right
+    held: [bool; 4],
+    pub speeds: SpeedModes,
+    // precision and turbo keys held
+    precision: bool,
+    turbo: bool,
+    /// The mode of the toggle keys, kept in the settings.
+    pub toggled_speed: Speed,
+    /// The mode of the current layer, from `LAYER_SPEEDS`.
+    pub layer_speed: Speed,
+    move_ticks: u16,
+    // pixels per tick
+    velocity: (f32, f32),
+    // the fractions of a pixel not sent yet
+    remainder: (f32, f32),
+    pub wheel: Wheel,
+    /// Wheel units per notch the host asked for with the resolution
+    /// multiplier, 1 without hi-res scrolling.
+    pub wheel_multiplier: u8,
+    // direction and if vertical
+    scroll: Option<(i8, bool)>,
+    scroll_ticks: u16,
+    // ms per notch, goes down while scrolling
+    scroll_interval: f32,
+    // the fractions of a unit not sent yet
+    scroll_units: f32,
+    // units since the last notch
+    scroll_progress: u8,
+}
+
+
+impl Default for Mouse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mouse {
+    pub fn new() -> Self {
+        Self {
+            report: WheelMouseReport::default(),
+            warp: Warp::new(),
+            active: true,
+            buttons: 0,
+            drag_lock: false,
+            motion: MOUSE_MOTION,
+            held: [false; 4],
+            speeds: MOUSE_SPEEDS,
+            precision: false,
+            turbo: false,
+            toggled_speed: Speed::Normal,
+            layer_speed: Speed::Normal,
+            move_ticks: 0,
+            velocity: (0., 0.),
+            remainder: (0., 0.),
+            wheel: MOUSE_WHEEL,
+            wheel_multiplier: 1,
+            scroll: None,
+            scroll_ticks: 0,
+            scroll_interval: 0.,
+            scroll_units: 0.,
+            scroll_progress: 0,
+        }
+    }
+
+    pub fn mouse_tick(&mut self) {
+        self.move_tick();
+        self.scroll_tick();
+    }
+
+    fn move_tick(&mut self) {
+        let dir = |neg: bool, pos: bool| pos as i8 as f32 - neg as i8 as f32;
+        let [up, down, left, right] = self.held;
+        let (x, y) = (dir(left, right), dir(up, down));
+        let moving = x != 0. || y != 0.;
+
+        // the same speed in every direction
+        let norm = if x != 0. && y != 0. { FRAC_1_SQRT_2 } else { 1. };
+
+        let m = self.motion;
+        let boost = self.speed_factor();
+        let (start, max) = (m.start_speed as f32 / 1000. * boost, m.max_speed as f32 / 1000. * boost);
+        let t = self.move_ticks.min(m.time_to_max) as f32 / m.time_to_max.max(1) as f32;
+
+        self.velocity = match m.profile {
+            Profile::Inertia => {
+                let step = max / m.time_to_max.max(1) as f32;
+                let approach = |v: f32, target: f32| match v < target {
+                    true => (v + step).min(target),
+                    false => (v - step).max(target),
+                };
+                (approach(self.velocity.0, x * norm * max), approach(self.velocity.1, y * norm * max))
+            },
+            profile => {
+                let speed = match profile {
+                    Profile::Constant => max,
+                    Profile::Kinetic => start + (max - start) * t * t,
+                    _ => start + (max - start) * t,
+                };
+                (x * norm * speed, y * norm * speed)
+            },
+        };
+
+        if !moving {
+            self.move_ticks = 0;
+            if self.velocity == (0., 0.) {
+                self.remainder = (0., 0.);
+            }
+        } else {
+            // the first tick moves a whole pixel, so taps are precise
+            if self.move_ticks == 0 {
+                self.remainder = (x, y);
+            }
+            self.move_ticks = self.move_ticks.saturating_add(1);
+        }
+
+        let (x, y) = (take_pixels(&mut self.remainder.0, self.velocity.0), take_pixels(&mut self.remainder.1, self.velocity.1));
+        self.report.x = self.report.x.saturating_add(x);
+        self.report.y = self.report.y.saturating_add(y);
+    }
+
+    fn scroll_tick(&mut self) {
+        let Some((dir, vert)) = self.scroll else { return };
+
+        self.scroll_ticks = self.scroll_ticks.saturating_add(1);
+        if self.scroll_ticks < self.wheel.delay {
+            return;
+        }
+
+        // in hi-res mode the notch is spread over the interval
+        let per_notch = self.wheel_multiplier.max(1);
+        self.scroll_units += self.speed_factor() * match self.scroll_ticks == self.wheel.delay {
+            true => per_notch as f32,
+            false => per_notch as f32 / self.scroll_interval,
+        };
+
+        let units = (self.scroll_units as i32).clamp(0, 127) as u8;
+        self.scroll_units -= units as f32;
+
+        self.scroll_progress = self.scroll_progress.saturating_add(units);
+        while self.scroll_progress >= per_notch {
+            self.scroll_progress -= per_notch;
+            let faster = self.scroll_interval * (100 - self.wheel.accel.min(100)) as f32 / 100.;
+            self.scroll_interval = faster.max(self.wheel.min_interval.max(1) as f32);
+        }
+
+        self.add_wheel(dir * units as i8, vert);
+    }
+
+    fn add_wheel(&mut self, units: i8, vert: bool) {
+        let wheel = match vert {
+            true => &mut self.report.vertical_wheel,
+            false => &mut self.report.horizontal_wheel,
+        };
+        *wheel = wheel.saturating_add(units);
+    }
+
+    /// The mode in effect: a held key, then a toggled mode, then the one
+    /// of the layer.
+    pub fn speed(&self) -> Speed {
+        match (self.precision, self.turbo) {
+            (true, _) => Speed::Precision,
+            (_, true) => Speed::Turbo,
+            _ if self.toggled_speed != Speed::Normal => self.toggled_speed,
+            _ => self.layer_speed,
+        }
+    }
+
+    /// What the motion and scroll are multiplied by.
+    pub fn speed_factor(&self) -> f32 {
+        match self.speed() {
+            Speed::Normal => 1.,
+            Speed::Precision => self.speeds.precision as f32 / 100.,
+            Speed::Turbo => self.speeds.turbo as f32 / 100.,
+        }
+    }
+
+    /// The mode for the OLED, while it isn't the normal one.
+    pub fn speed_label(&self) -> Option<String<8>> {
+        let label = match self.speed() {
+            Speed::Normal => return None,
+            Speed::Precision => "Pre",
+            Speed::Turbo => "Tur",
+        };
+        let mut txt = String::new();
+        let _ = txt.push_str(label);
+        Some(txt)
+    }
+
+    /// The movement in `report` reached the host, the next one starts
+    /// from zero.
+    pub fn sent(&mut self) {
+        self.report.x = 0;
+        self.report.y = 0;
+        self.report.vertical_wheel = 0;
+        self.report.horizontal_wheel = 0;
+    }
+
+    pub fn handle_mouse_btn(&mut self, action: &MAction, is_pressed: bool) {
+        match action {
+            MAction::Left => self.button(0x1, is_pressed),
+            MAction::Right => self.button(0x2, is_pressed),
+            MAction::Middle => self.button(0x4, is_pressed),
+            MAction::Back => self.button(0x8, is_pressed),
+            MAction::Forward => self.button(0x10, is_pressed),
+            MAction::DragLock if is_pressed => {
+                self.drag_lock = !self.drag_lock;
+                self.button(0, false);
+            },
+            MAction::DragLock => (),
+
+            MAction::Move(dir) => {
+                let i = match dir {
+                    Dir::Up => 0,
+                    Dir::Down => 1,
+                    Dir::Left => 2,
+                    Dir::Right => 3,
+                };
+                self.held[i] = is_pressed;
+            },
+            MAction::Speedup => self.turbo = is_pressed,
+            MAction::Precision => self.precision = is_pressed,
+            MAction::ToggleSpeed(speed) if is_pressed => {
+                self.toggled_speed = match self.toggled_speed == *speed {
+                    true => Speed::Normal,
+                    false => *speed,
+                };
+            },
+            MAction::ToggleSpeed(_) => (),
+
+            MAction::Scroll(dir) if is_pressed => {
+                let (dir, vert) = match dir {
+                    Dir::Up => (1, true),
+                    Dir::Down => (-1, true),
+                    Dir::Left => (-1, false),
+                    Dir::Right => (1, false),
+                };
+                self.scroll = Some((dir, vert));
+                self.scroll_ticks = 0;
+                self.scroll_interval = self.wheel.interval.max(1) as f32;
+                self.scroll_units = 0.;
+                self.scroll_progress = 0;
+
+                // a notch right away, at least a unit of it
+                let units = (self.wheel_multiplier.max(1) as f32 * self.speed_factor()).round().clamp(1., 127.);
+                self.add_wheel(dir * units as i8, vert);
+            },
+            MAction::Scroll(_) => self.scroll = None,
+
+            MAction::Warp(grid) if is_pressed => self.warp.toggle(*grid),
+            MAction::Warp(_) => (),
+
+            MAction::ToggleActive if is_pressed => self.active = !self.active,
+            MAction::ToggleActive => (),
+        }
+    }
+
+    fn button(&mut self, bit: u8, is_pressed: bool) {
+        match is_pressed {
+            true => self.buttons |= bit,
+            false => self.buttons &= !bit,
+        }
+
+        // a click drops what was dragged
+        if is_pressed && bit == 0x1 {
+            self.drag_lock = false;
+        }
+        self.report.buttons = self.buttons | self.drag_lock as u8;
+    }
+}
+
+// the whole pixels of `remainder` after adding `velocity`, the fraction
+// stays for the next ticks
+fn take_pixels(remainder: &mut f32, velocity: f32) -> i8 {
+    *remainder += velocity;
+    let px = (*remainder as i32).clamp(-127, 127);
+    *remainder -= px as f32;
+    px as i8
+}
@@ -0,0 +1,100 @@
+use {
+    embedded_graphics::{
+        mono_font::{iso_8859_16::FONT_10X20 as FONT, MonoTextStyleBuilder, MonoTextStyle},
+        pixelcolor::BinaryColor,
+        prelude::*,
+        image::{Image, ImageRaw},
+        text::{Baseline, Text},
+    },
+    ufmt::uwrite,
+    crate::os::HostOs,
+};
+
+const TEXT_STYLE: MonoTextStyle<BinaryColor> = MonoTextStyleBuilder::new()
+    .font(&FONT)
+    .text_color(BinaryColor::On)
+    .build();
+
+/// What the 32x128 (rotated) OLED shows, redrawn only when it changes.
+pub struct Screen {
+    prev: Option<(usize, bool, HostOs, heapless::String<8>)>,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Self { prev: None }
+    }
+
+    /// Draws the layer, the host OS (or `info` while there is one) and the
+    /// half talking to the host, returns false when nothing changed since
+    /// the last call.
+    pub fn draw<D>(
+        &mut self,
+        display: &mut D,
+        curr_layer: usize,
+        uru: bool,
+        os: HostOs,
+        info: Option<&str>,
+    ) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+    {
+        let mut info_txt: heapless::String<8> = heapless::String::new();
+        let _ = info_txt.push_str(info.unwrap_or_default());
+
+        let state = Some((curr_layer, uru, os, info_txt.clone()));
+        if self.prev == state {
+            return Ok(false);
+        }
+        self.prev = state;
+
+        let mut txt: heapless::String<32> = heapless::String::new();
+
+
+        {
+            let img: ImageRaw<BinaryColor> = ImageRaw::new(include_bytes!("../../img.raw"), 32);
+            Image::new(&img, Point::new(0, 0)).draw(display)?;
+        }
+
+        {
+            let _ = uwrite!(&mut txt, "L {}", curr_layer);
+
+            Text::with_baseline(
+                &txt,
+                Point::new(2, 55),
+                TEXT_STYLE,
+                Baseline::Top
+            ).draw(display)?;
+        }
+
+        {
+            Text::with_baseline(
+                if info_txt.is_empty() { os.name() } else { &info_txt },
+                Point::new(2, 78),
+                TEXT_STYLE,
+                Baseline::Top
+            ).draw(display)?;
+        }
+
+        {
+            txt.clear(); 
+            let _ = uwrite!(&mut txt, "{}", 
+                if uru { "-->" } else { "<--" }
+            );
+
+            Text::with_baseline(&txt,
+                Point::new(2, 100),
+                TEXT_STYLE,
+                Baseline::Top
+            ).draw(display)?;
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,162 @@
+use {
+    keyberon::key_code::KeyCode,
+    heapless::Vec,
+    usb_device::{
+        class_prelude::*,
+        control::{Request, RequestType},
+        descriptor::descriptor_type,
+    },
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HostOs {
+    Unknown,
+    Linux,
+    Windows,
+    MacOs,
+    Ios,
+}
+
+impl HostOs {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HostOs::Unknown => "?",
+            HostOs::Linux => "LNX",
+            HostOs::Windows => "WIN",
+            HostOs::MacOs => "MAC",
+            HostOs::Ios => "IOS",
+        }
+    }
+
+    fn is_apple(&self) -> bool {
+        matches!(self, HostOs::MacOs | HostOs::Ios)
+    }
+}
+
+/// Keys sent by a shortcut on apple hosts and on everything else.
+pub struct Shortcut {
+    pub pc: &'static [KeyCode],
+    pub mac: &'static [KeyCode],
+}
+
+// hosts ask for the string descriptors with different lengths
+// while enumerating, counting them is enough to tell them apart
+pub struct OsDetect {
+    count: u8,
+    cnt_02: u8,
+    cnt_04: u8,
+    cnt_ff: u8,
+    last_length: u16,
+    manual: Option<HostOs>,
+}
+
+impl Default for OsDetect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OsDetect {
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            cnt_02: 0,
+            cnt_04: 0,
+            cnt_ff: 0,
+            last_length: 0,
+            manual: None,
+        }
+    }
+
+    pub fn host(&self) -> HostOs {
+        self.manual.unwrap_or_else(|| self.detected())
+    }
+
+    fn detected(&self) -> HostOs {
+        if self.count < 3 {
+            return HostOs::Unknown;
+        }
+
+        if self.cnt_ff >= 2 && self.cnt_04 >= 1 {
+            HostOs::Windows
+        } else if self.count == self.cnt_ff {
+            HostOs::Linux
+        } else if self.count == 5 && self.last_length == 0xFF && self.cnt_ff == 1 && self.cnt_02 == 2 {
+            HostOs::MacOs
+        } else if self.count == 4 && self.cnt_ff == 0 && self.cnt_02 == 2 {
+            HostOs::Ios
+        } else if self.cnt_ff >= 2 && self.cnt_02 == 0 && self.cnt_04 == 0 {
+            HostOs::Linux
+        } else {
+            HostOs::Unknown
+        }
+    }
+
+    // auto -> linux -> windows -> macos -> auto
+    pub fn cycle_manual(&mut self) {
+        self.manual = match self.manual {
+            None => Some(HostOs::Linux),
+            Some(HostOs::Linux) => Some(HostOs::Windows),
+            Some(HostOs::Windows) => Some(HostOs::MacOs),
+            _ => None,
+        }
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for OsDetect {
+    fn reset(&mut self) {
+        *self = Self { manual: self.manual, ..Self::new() };
+    }
+
+    // only looks at the request, the device still answers it
+    fn control_in(&mut self, xfer: ControlIn<B>) {
+        let req = xfer.request();
+
+        if
+            req.request_type == RequestType::Standard &&
+            req.request == Request::GET_DESCRIPTOR &&
+            (req.value >> 8) as u8 == descriptor_type::STRING
+        {
+            self.count = self.count.saturating_add(1);
+            self.last_length = req.length;
+
+            match req.length {
+                0x02 => self.cnt_02 = self.cnt_02.saturating_add(1),
+                0x04 => self.cnt_04 = self.cnt_04.saturating_add(1),
+                0xFF => self.cnt_ff = self.cnt_ff.saturating_add(1),
+                _ => (),
+            }
+        }
+    }
+}
+
+pub struct Shortcuts {
+    held: Vec<&'static Shortcut, 4>,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shortcuts {
+    pub fn new() -> Self {
+        Self { held: Vec::new() }
+    }
+
+    pub fn press(&mut self, sc: &'static Shortcut) {
+        let _ = self.held.push(sc);
+    }
+
+    pub fn release(&mut self, sc: &'static Shortcut) {
+        self.held.retain(|h| !core::ptr::eq(*h, sc));
+    }
+
+    pub fn keys(&self, os: HostOs) -> impl Iterator<Item = KeyCode> + '_ {
+        self.held
+            .iter()
+            .flat_map(move |sc| if os.is_apple() { sc.mac } else { sc.pc })
+            .copied()
+    }
+}
@@ -0,0 +1,57 @@
+use {
+    keyberon::key_code::KeyCode,
+    heapless::Vec,
+    crate::layout::KEY_OVERRIDES,
+};
+
+pub const CTRL: u8 = 0x11;
+pub const SHIFT: u8 = 0x22;
+pub const ALT: u8 = 0x44;
+pub const GUI: u8 = 0x88;
+
+/// Replaces `trigger` with `replacement` while any modifier in `mods` is
+/// held on one of `layers` (all layers when empty). The matched modifiers
+/// are not sent, `replacement` can list its own if it needs them.
+pub struct KeyOverride {
+    pub mods: u8,
+    pub trigger: KeyCode,
+    pub replacement: &'static [KeyCode],
+    pub layers: &'static [usize],
+}
+
+impl KeyOverride {
+    fn matches(&self, mods: u8, layer: usize) -> bool {
+        mods & self.mods != 0 &&
+        (self.layers.is_empty() || self.layers.contains(&layer))
+    }
+}
+
+pub type Keys = Vec<KeyCode, 32>;
+
+/// Collects `keys`, unlike `collect` it doesn't panic when they don't
+/// fit, the ones past the capacity are dropped like a rollover.
+pub fn collect_keys(keys: impl IntoIterator<Item = KeyCode>) -> Keys {
+    let mut collected = Keys::new();
+    keys.into_iter().for_each(|k| { let _ = collected.push(k); });
+    collected
+}
+
+pub fn apply(keys: &mut Keys, layer: usize) {
+    let mods = keys.iter().fold(0, |m, k| m | k.as_modifier_bit());
+    let mut suppressed = 0;
+    let mut added = Keys::new();
+
+    KEY_OVERRIDES
+        .iter()
+        .filter(|o| o.matches(mods, layer) && keys.contains(&o.trigger))
+        .for_each(|o| {
+            suppressed |= o.mods;
+            keys.retain(|k| *k != o.trigger);
+            o.replacement.iter().for_each(|k| { let _ = added.push(*k); });
+        });
+
+    if suppressed != 0 {
+        keys.retain(|k| k.as_modifier_bit() & suppressed == 0);
+        added.into_iter().for_each(|k| { let _ = keys.push(k); });
+    }
+}
@@ -0,0 +1,150 @@
+use {
+    keyberon::layout::Event,
+    crate::{
+        layout::{AUTO_MOUSE_COORD, POINTING},
+        mouse::Mouse,
+    },
+};
+
+/// A trackball, trackpad or stick read every tick, the hardware side
+/// implements it like `Storage`.
+pub trait Sensor {
+    /// Motion since the last read, in counts at `PointingConfig::sensor_cpi`.
+    fn motion(&mut self) -> Result<(i16, i16), ()>;
+}
+
+pub enum PAction {
+    /// Slower, at the sniping CPI, while held.
+    Sniping,
+    /// Motion scrolls instead of moving the pointer while held.
+    DragScroll,
+}
+
+/// CPIs are counts per inch moved, the pointer moves a pixel per count.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PointingConfig {
+    /// What the sensor reports at, the same on both halves.
+    pub sensor_cpi: u16,
+    pub cpi: u16,
+    pub sniping_cpi: u16,
+    /// Counts per wheel notch while drag scrolling.
+    pub drag_scroll_counts: u16,
+    /// Ms without motion before the layer of `AUTO_MOUSE_COORD` is let
+    /// go, 0 turns the auto mouse layer off.
+    pub auto_mouse_timeout: u16,
+}
+
+/// The motion of either half goes through here to `Mouse::report`.
+pub struct Pointing {
+    pub config: PointingConfig,
+    sniping: bool,
+    drag_scroll: bool,
+    // counts times cpi not sent yet, and counts toward the next notch
+    remainder: (i32, i32),
+    scroll: (i32, i32),
+    // ticks left of the auto mouse layer while its key is pressed
+    auto_mouse: Option<u16>,
+}
+
+impl Default for Pointing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pointing {
+    pub fn new() -> Self {
+        Self {
+            config: POINTING,
+            sniping: false,
+            drag_scroll: false,
+            remainder: (0, 0),
+            scroll: (0, 0),
+            auto_mouse: None,
+        }
+    }
+
+    /// Adds the motion of a sensor to `mouse`, returns the press of the
+    /// auto mouse layer key when it starts.
+    pub fn motion(&mut self, (dx, dy): (i16, i16), mouse: &mut Mouse) -> Option<Event> {
+        if (dx, dy) == (0, 0) {
+            return None;
+        }
+
+        let c = self.config;
+        let cpi = if self.sniping { c.sniping_cpi } else { c.cpi } as f32;
+        let cpi = (cpi * mouse.speed_factor()) as i32;
+        let sensor_cpi = c.sensor_cpi.max(1) as i32;
+
+        let mut scale = |rem: &mut i32, d: i16| {
+            *rem += d as i32 * cpi;
+            let px = *rem / sensor_cpi;
+            *rem -= px * sensor_cpi;
+            px
+        };
+        let (x, y) = (scale(&mut self.remainder.0, dx), scale(&mut self.remainder.1, dy));
+
+        if self.drag_scroll {
+            // in hi-res mode a notch is several wheel units
+            let per_unit = (c.drag_scroll_counts / mouse.wheel_multiplier.max(1) as u16).max(1) as i32;
+            let mut units = |acc: &mut i32, d: i32| {
+                *acc += d;
+                let u = *acc / per_unit;
+                *acc -= u * per_unit;
+                u.clamp(-127, 127) as i8
+            };
+
+            // moving up scrolls up
+            let (h, v) = (units(&mut self.scroll.0, x), units(&mut self.scroll.1, -y));
+            mouse.report.horizontal_wheel = mouse.report.horizontal_wheel.saturating_add(h);
+            mouse.report.vertical_wheel = mouse.report.vertical_wheel.saturating_add(v);
+        } else {
+            mouse.report.x = mouse.report.x.saturating_add(x.clamp(-127, 127) as i8);
+            mouse.report.y = mouse.report.y.saturating_add(y.clamp(-127, 127) as i8);
+        }
+
+        self.keep_auto_mouse();
+        (self.auto_mouse.is_none() && c.auto_mouse_timeout > 0).then(|| {
+            self.auto_mouse = Some(c.auto_mouse_timeout);
+            Event::Press(AUTO_MOUSE_COORD.0, AUTO_MOUSE_COORD.1)
+        })
+    }
+
+    /// Mouse keys keep the auto mouse layer on.
+    pub fn keep_auto_mouse(&mut self) {
+        if let Some(t) = self.auto_mouse.as_mut() {
+            *t = self.config.auto_mouse_timeout;
+        }
+    }
+
+    pub fn auto_mouse_active(&self) -> bool {
+        self.auto_mouse.is_some()
+    }
+
+    /// Returns the release of the auto mouse layer key once it timed out,
+    /// held mouse buttons keep it.
+    pub fn tick(&mut self, mouse: &Mouse) -> Option<Event> {
+        if mouse.report.buttons != 0 {
+            self.keep_auto_mouse();
+        }
+
+        let t = self.auto_mouse.as_mut()?;
+        *t = t.saturating_sub(1);
+        if *t > 0 {
+            return None;
+        }
+
+        self.auto_mouse = None;
+        Some(Event::Release(AUTO_MOUSE_COORD.0, AUTO_MOUSE_COORD.1))
+    }
+
+    pub fn handle_action(&mut self, action: &PAction, is_pressed: bool) {
+        match action {
+            PAction::Sniping => self.sniping = is_pressed,
+            PAction::DragScroll => {
+                self.drag_scroll = is_pressed;
+                self.scroll = (0, 0);
+            },
+        }
+    }
+}
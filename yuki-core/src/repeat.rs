@@ -0,0 +1,69 @@
+use {
+    keyberon::key_code::KeyCode,
+    crate::{
+        overrides::Keys,
+        layout::ALT_REPEAT,
+    },
+};
+
+pub struct Repeat {
+    // last tapped key together with the modifiers held with it
+    last: Keys,
+    prev: Keys,
+    // keys added to the report while a repeat key is held
+    sending: Keys,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repeat {
+    pub fn new() -> Self {
+        Self {
+            last: Keys::new(),
+            prev: Keys::new(),
+            sending: Keys::new(),
+        }
+    }
+
+    pub fn press(&mut self, alt: bool) {
+        self.sending = match alt {
+            false => self.last.clone(),
+            true => alt_of(&self.last).iter().copied().collect(),
+        };
+    }
+
+    pub fn release(&mut self) {
+        self.sending.clear();
+    }
+
+    /// Records newly pressed keys from the report and adds the
+    /// keys of a held repeat key to it.
+    pub fn update(&mut self, keys: &mut Keys) {
+        if let Some(&new) = keys.iter().rev().find(|&&k| !k.is_modifier() && !self.prev.contains(&k)) {
+            self.last = keys.iter().filter(|k| k.is_modifier()).copied().collect();
+            let _ = self.last.push(new);
+        }
+
+        self.prev = keys.clone();
+        self.sending.iter().for_each(|k| { let _ = keys.push(*k); });
+    }
+}
+
+fn same(a: &[KeyCode], b: &[KeyCode]) -> bool {
+    a.len() == b.len() && a.iter().all(|k| b.contains(k))
+}
+
+fn alt_of(keys: &[KeyCode]) -> &'static [KeyCode] {
+    ALT_REPEAT
+        .iter()
+        .find_map(|(a, b)| {
+            if same(a, keys) { Some(*b) }
+            else if same(b, keys) { Some(*a) }
+            else { None }
+        })
+        .unwrap_or(&[])
+}
@@ -0,0 +1,136 @@
+use {
+    heapless::Deque,
+    usbd_human_interface_device::{
+        device::{consumer::MultipleConsumerReport, mouse::WheelMouseReport},
+        page::Consumer,
+    },
+    crate::{
+        hid::SystemControl,
+        keyboard::Keyboard,
+        overrides::{self, Keys},
+        warp::AbsReport,
+    },
+};
+
+/// Ticks between reports that only move the mouse, its endpoint is
+/// polled every 10 ms anyway.
+pub const MOTION_INTERVAL: u16 = 10;
+const QUEUE_LEN: usize = 16;
+
+pub enum Report {
+    Keyboard(Keys),
+    Mouse(WheelMouseReport),
+    Abs(AbsReport),
+    Consumer(MultipleConsumerReport),
+    System(Option<SystemControl>),
+}
+
+/// Queues the reports of the devices when they change, in the order of
+/// the changes, so a tap shorter than the polling interval or a shift
+/// click still reaches the host as it was typed.
+pub struct Reports {
+    queue: Deque<Report, QUEUE_LEN>,
+    // what was queued last of each, new reports are only made on changes
+    keys: Keys,
+    buttons: u8,
+    consumer: [Consumer; 4],
+    system: Option<SystemControl>,
+    // ticks since the last mouse report
+    motion_ticks: u16,
+}
+
+impl Default for Reports {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reports {
+    pub fn new() -> Self {
+        Self {
+            queue: Deque::new(),
+            keys: Keys::new(),
+            buttons: 0,
+            consumer: [Consumer::Unassigned; 4],
+            system: None,
+            motion_ticks: 0,
+        }
+    }
+
+    /// Queues what changed since the last tick. What doesn't fit stays
+    /// in the keyboard (the mouse motion keeps adding up) for the next
+    /// ticks.
+    pub fn update(&mut self, kb: &mut Keyboard) {
+        let keys = overrides::collect_keys(kb.keyboard_keys());
+        if keys != self.keys && self.queue.push_back(Report::Keyboard(keys.clone())).is_ok() {
+            self.keys = keys;
+        }
+
+        let consumer = kb.consumer_report();
+        if consumer.codes != self.consumer {
+            let codes = consumer.codes;
+            if self.queue.push_back(Report::Consumer(consumer)).is_ok() {
+                self.consumer = codes;
+            }
+        }
+
+        let system = kb.system_control();
+        if system != self.system && self.queue.push_back(Report::System(system)).is_ok() {
+            self.system = system;
+        }
+
+        self.update_mouse(kb);
+    }
+
+    /// For the ticks this half doesn't send: the mouse motion is dropped
+    /// instead of adding up, and everything is sent again once it does.
+    pub fn skip(&mut self, kb: &mut Keyboard) {
+        kb.mouse.sent();
+        kb.mouse.warp.sent();
+        *self = Self::new();
+    }
+
+    fn update_mouse(&mut self, kb: &mut Keyboard) {
+        let mouse = &mut kb.mouse;
+        // the mouse interfaces stay quiet while it is off
+        if !mouse.active {
+            mouse.sent();
+            mouse.warp.sent();
+            return;
+        }
+
+        self.motion_ticks = self.motion_ticks.saturating_add(1);
+        let r = mouse.report;
+        let moved = r.x != 0 || r.y != 0 || r.vertical_wheel != 0 || r.horizontal_wheel != 0;
+
+        if r.buttons != self.buttons || (moved && self.motion_ticks >= MOTION_INTERVAL) {
+            if self.queue.push_back(Report::Mouse(r)).is_ok() {
+                self.buttons = r.buttons;
+                self.motion_ticks = 0;
+                mouse.sent();
+            }
+        }
+
+        if let Some(abs) = mouse.warp.report() {
+            if self.queue.push_back(Report::Abs(abs)).is_ok() {
+                mouse.warp.sent();
+            }
+        }
+    }
+
+    /// Hands the queued reports to `write` in order, until it returns
+    /// false because the endpoint is still busy, that report is tried
+    /// again next time.
+    pub fn send(&mut self, mut write: impl FnMut(&Report) -> bool) {
+        while let Some(r) = self.queue.front() {
+            if !write(r) {
+                return;
+            }
+            self.queue.pop_front();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
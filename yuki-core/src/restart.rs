@@ -0,0 +1,56 @@
+// how long a restart key has to be held, in ticks
+const CONFIRM_TICKS: u16 = 1000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Restart {
+    /// Into the STM32 system bootloader (DFU).
+    Bootloader,
+    Reset,
+}
+
+/// Restart keys only act once they are held long enough, so they are not
+/// hit by accident.
+pub struct HoldToConfirm {
+    held: Option<(Restart, u16)>,
+}
+
+impl Default for HoldToConfirm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HoldToConfirm {
+    pub fn new() -> Self {
+        Self { held: None }
+    }
+
+    pub fn press(&mut self, restart: Restart) {
+        self.held = Some((restart, 0));
+    }
+
+    pub fn release(&mut self, restart: Restart) {
+        if self.held.is_some_and(|(r, _)| r == restart) {
+            self.held = None;
+        }
+    }
+
+    /// The restart whose key is held, not yet long enough.
+    pub fn pending(&self) -> Option<Restart> {
+        self.held.map(|(r, _)| r)
+    }
+
+    /// Returns the restart once its key has been held long enough.
+    pub fn tick(&mut self) -> Option<Restart> {
+        let (restart, ticks) = self.held.as_mut()?;
+
+        *ticks += 1;
+        if *ticks < CONFIRM_TICKS {
+            return None;
+        }
+
+        let restart = *restart;
+        self.held = None;
+        Some(restart)
+    }
+}
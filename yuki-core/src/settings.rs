@@ -0,0 +1,102 @@
+use crate::{holdtap::MAX_TERMS, mouse::Speed};
+
+// every save appends a record to the storage so it only has to be
+// erased when its full
+const RECORD_LEN: usize = 16;
+const MAGIC: u8 = 0x59;
+
+/// Where the settings records are kept, a flash sector on the keyboard.
+/// Erased bytes read as 0xFF.
+pub trait Storage {
+    fn read(&self) -> &[u8];
+    fn erase(&mut self) -> Result<(), ()>;
+    fn program(&mut self, offset: usize, bytes: &[u8]) -> Result<(), ()>;
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Settings {
+    pub default_layer: u8,
+    /// Hold-tap terms in ms, u16::MAX for the default one.
+    pub hold_tap_terms: [u16; MAX_TERMS],
+    /// Toggled speed mode of the mouse.
+    pub mouse_speed: Speed,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Settings {
+    pub const fn new() -> Self {
+        Self { default_layer: 0, hold_tap_terms: [u16::MAX; MAX_TERMS], mouse_speed: Speed::Normal }
+    }
+
+    fn to_bytes(&self) -> [u8; RECORD_LEN] {
+        let mut b = [0xFF; RECORD_LEN];
+        b[0] = MAGIC;
+        b[1] = self.default_layer;
+        for (i, t) in self.hold_tap_terms.iter().enumerate() {
+            b[2 + i * 2..4 + i * 2].copy_from_slice(&t.to_le_bytes());
+        }
+        b[10] = match self.mouse_speed {
+            Speed::Normal => 0,
+            Speed::Precision => 1,
+            Speed::Turbo => 2,
+        };
+        b
+    }
+
+    fn from_bytes(b: &[u8]) -> Option<Self> {
+        if b[0] != MAGIC {
+            return None;
+        }
+
+        // records saved before the terms were added have 0xFF there
+        let mut hold_tap_terms = [u16::MAX; MAX_TERMS];
+        for (i, t) in hold_tap_terms.iter_mut().enumerate() {
+            *t = u16::from_le_bytes([b[2 + i * 2], b[3 + i * 2]]);
+        }
+
+        // and the ones before the speed modes too
+        let mouse_speed = match b[10] {
+            1 => Speed::Precision,
+            2 => Speed::Turbo,
+            _ => Speed::Normal,
+        };
+
+        Some(Self { default_layer: b[1], hold_tap_terms, mouse_speed })
+    }
+
+    /// Returns the last saved settings, or the defaults if nothing was saved.
+    pub fn load(storage: &impl Storage) -> Self {
+        records(storage)
+            .take_while(|r| r[0] != 0xFF)
+            .last()
+            .and_then(Self::from_bytes)
+            .unwrap_or(Self::new())
+    }
+
+    /// Appends the settings, returns true if the storage had to be erased.
+    pub fn store(&self, storage: &mut impl Storage) -> bool {
+        let free = records(storage).position(|r| r.iter().all(|b| *b == 0xFF));
+
+        let (offset, erased) = match free {
+            Some(i) => (i * RECORD_LEN, false),
+            None => {
+                if storage.erase().is_err() {
+                    return true;
+                }
+                (0, true)
+            }
+        };
+
+        let _ = storage.program(offset, &self.to_bytes());
+        erased
+    }
+}
+
+fn records(storage: &impl Storage) -> impl Iterator<Item = &[u8]> {
+    storage.read().chunks(RECORD_LEN)
+}
@@ -0,0 +1,38 @@
+/// Whether the host put the bus to sleep, set by the half on USB and
+/// sent to the other one.
+#[derive(Default)]
+pub struct Suspend {
+    suspended: bool,
+    // a key was pressed while suspended
+    wake: bool,
+}
+
+impl Suspend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true when it changed, so the other half is told.
+    pub fn set(&mut self, suspended: bool) -> bool {
+        let changed = self.suspended != suspended;
+        self.suspended = suspended;
+        if changed {
+            self.wake = false;
+        }
+        changed
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    pub fn press(&mut self) {
+        self.wake |= self.suspended;
+    }
+
+    /// True once per key press while suspended, the half on USB wakes
+    /// the host if it allowed remote wakeup.
+    pub fn take_wake(&mut self) -> bool {
+        core::mem::take(&mut self.wake)
+    }
+}
@@ -0,0 +1,76 @@
+use {
+    keyberon::{action::Action, layout::Event},
+    crate::layout::{action_at, ActiveLayers, CustomAction, SWAP_HANDS},
+};
+
+pub enum SwapAction {
+    Momentary,
+    Toggle,
+    OneShot,
+}
+
+pub struct SwapHands {
+    toggled: bool,
+    held: bool,
+    oneshot: bool,
+    // bit per (i, j) of the keys that were pressed swapped,
+    // so they are released swapped even if the swap ended
+    swapped: u64,
+}
+
+impl Default for SwapHands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SwapHands {
+    pub fn new() -> Self {
+        Self {
+            toggled: false,
+            held: false,
+            oneshot: false,
+            swapped: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.toggled || self.held || self.oneshot
+    }
+
+    /// Maps `event` to its position in `SWAP_HANDS` while swapped.
+    pub fn event(&mut self, event: Event, layers: ActiveLayers) -> Event {
+        let (i, j) = event.coord();
+        let bit = 1 << (i as u64 * 12 + j as u64);
+
+        match event {
+            Event::Press(..) => {
+                if !self.is_active() || matches!(action_at((i, j), layers), Action::Custom(CustomAction::Swap(_))) {
+                    return event;
+                }
+
+                self.oneshot = false;
+                self.swapped |= bit;
+            },
+            Event::Release(..) => {
+                if self.swapped & bit == 0 {
+                    return event;
+                }
+
+                self.swapped &= !bit;
+            },
+        }
+
+        let (si, sj) = SWAP_HANDS[i as usize][j as usize];
+        event.transform(|_, _| (si, sj))
+    }
+
+    pub fn handle_action(&mut self, action: &SwapAction, is_pressed: bool) {
+        match (action, is_pressed) {
+            (SwapAction::Momentary, _) => self.held = is_pressed,
+            (SwapAction::Toggle, true) => self.toggled = !self.toggled,
+            (SwapAction::OneShot, true) => self.oneshot = true,
+            _ => (),
+        }
+    }
+}
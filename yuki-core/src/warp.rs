@@ -0,0 +1,156 @@
+use {
+    keyberon::layout::Event,
+    heapless::{String, Vec},
+    ufmt::uwrite,
+    crate::layout::{WARP_BACK, WARP_CLICK, WARP_GRID_3, WARP_GRID_4},
+};
+
+/// Largest coordinate of the absolute pointer, on both axes.
+pub const ABS_MAX: u16 = 32767;
+const MAX_LEVELS: usize = 8;
+
+/// Report of the absolute pointer, sent next to the wheel mouse one.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct AbsReport {
+    pub buttons: u8,
+    pub x: u16,
+    pub y: u16,
+}
+
+// x, y, width, height
+type Area = (u16, u16, u16, u16);
+const SCREEN: Area = (0, 0, ABS_MAX, ABS_MAX);
+
+/// Keynav style warping: each key picks a cell of a grid over the
+/// screen, the pointer jumps to its center and the next key picks a cell
+/// of that cell. The cell keys are `WARP_GRID_3`/`WARP_GRID_4`,
+/// `WARP_BACK` goes back a level, `WARP_CLICK` clicks and stops warping
+/// and any other key only stops it.
+#[derive(Default)]
+pub struct Warp {
+    pub report: AbsReport,
+    // the size of the grid while warping
+    grid: Option<u8>,
+    // the picked cells, the last one is where the pointer is
+    areas: Vec<Area, MAX_LEVELS>,
+    pending: bool,
+    // bit per (i, j) of the keys pressed while warping,
+    // so their releases don't go to the layout either
+    taken: u64,
+}
+
+impl Warp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.grid.is_some()
+    }
+
+    /// Starts warping over the whole screen with a 3x3 or 4x4 grid, or
+    /// stops.
+    pub fn toggle(&mut self, grid: u8) {
+        self.grid = match self.grid {
+            Some(_) => None,
+            None => Some(grid.clamp(3, 4)),
+        };
+        self.areas.clear();
+    }
+
+    /// How many cells were picked.
+    pub fn level(&self) -> usize {
+        self.areas.len()
+    }
+
+    /// Takes the keys while warping, returns false for the events that
+    /// go on to the layout.
+    pub fn event(&mut self, event: Event) -> bool {
+        let coord = event.coord();
+        let bit = 1 << (coord.0 as u64 * 12 + coord.1 as u64);
+
+        match event {
+            Event::Press(..) => {
+                let Some(grid) = self.grid else { return false };
+                self.taken |= bit;
+
+                if coord == WARP_CLICK {
+                    self.set_buttons(0x1);
+                } else if coord == WARP_BACK {
+                    self.areas.pop();
+                    self.move_pointer();
+                } else if let Some((row, col)) = cell(grid, coord) {
+                    self.pick(grid, row, col);
+                } else {
+                    self.grid = None;
+                }
+            },
+            Event::Release(..) => {
+                if self.taken & bit == 0 {
+                    return false;
+                }
+                self.taken &= !bit;
+
+                if coord == WARP_CLICK {
+                    self.set_buttons(0);
+                    self.grid = None;
+                }
+            },
+        }
+        true
+    }
+
+    /// The report when it changed since the last one sent.
+    pub fn report(&self) -> Option<AbsReport> {
+        self.pending.then_some(self.report)
+    }
+
+    pub fn sent(&mut self) {
+        self.pending = false;
+    }
+
+    /// The grid level for the OLED while warping.
+    pub fn label(&self) -> Option<String<8>> {
+        self.grid?;
+
+        let mut txt = String::new();
+        let _ = uwrite!(&mut txt, "W {}", self.level());
+        Some(txt)
+    }
+
+    fn pick(&mut self, grid: u8, row: u16, col: u16) {
+        let (x, y, w, h) = self.areas.last().copied().unwrap_or(SCREEN);
+        let (w, h) = (w / grid as u16, h / grid as u16);
+
+        // past the last level the pointer stays in its cell
+        let _ = self.areas.push((x + col * w, y + row * h, w, h));
+        self.move_pointer();
+    }
+
+    fn move_pointer(&mut self) {
+        if let Some(&(x, y, w, h)) = self.areas.last() {
+            self.report.x = x + w / 2;
+            self.report.y = y + h / 2;
+            self.pending = true;
+        }
+    }
+
+    fn set_buttons(&mut self, buttons: u8) {
+        self.report.buttons = buttons;
+        self.pending = true;
+    }
+}
+
+// (row, col) in the grid of the key at `coord`
+fn cell(grid: u8, coord: (u8, u8)) -> Option<(u16, u16)> {
+    fn find<const N: usize>(keys: &[[(u8, u8); N]; N], coord: (u8, u8)) -> Option<(u16, u16)> {
+        keys.iter().enumerate().find_map(|(row, cols)| {
+            cols.iter().position(|&k| k == coord).map(|col| (row as u16, col as u16))
+        })
+    }
+
+    match grid {
+        3 => find(&WARP_GRID_3, coord),
+        _ => find(&WARP_GRID_4, coord),
+    }
+}
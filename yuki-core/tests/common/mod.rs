@@ -0,0 +1,9 @@
+use yuki_core::keyboard::Keyboard;
+
+/// Presses and releases the key, 5 ticks each.
+pub fn tap(kb: &mut Keyboard, (i, j): (u8, u8)) {
+    kb.press(i, j);
+    kb.ticks(5);
+    kb.release(i, j);
+    kb.ticks(5);
+}
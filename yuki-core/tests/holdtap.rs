@@ -0,0 +1,120 @@
+use {
+    keyberon::key_code::KeyCode::*,
+    yuki_core::{
+        holdtap::{HoldTaps, MAX_TERMS},
+        keyboard::Keyboard,
+    },
+};
+
+// CTRL_TAB and its default term
+const CTRL_TAB: (u8, u8) = (3, 8);
+const TERM: usize = 180;
+const ALT_ENTER: (u8, u8) = (3, 5);
+
+// on the mouse layer, held with (3, 9)
+const SELECT: (u8, u8) = (0, 7);
+const DECREASE: (u8, u8) = (0, 8);
+const INCREASE: (u8, u8) = (0, 9);
+
+fn settings_key(kb: &mut Keyboard, (i, j): (u8, u8)) {
+    kb.press(3, 9);
+    kb.tick();
+    kb.press(i, j);
+    kb.tick();
+    kb.release(i, j);
+    kb.release(3, 9);
+    kb.ticks(5);
+}
+
+// ticks until CTRL_TAB alone turns into LCtrl
+fn hold_after(kb: &mut Keyboard) -> usize {
+    kb.press(CTRL_TAB.0, CTRL_TAB.1);
+    let ticks = (1..1000).find(|_| { kb.tick(); kb.pressed(LCtrl) }).unwrap();
+    kb.release(CTRL_TAB.0, CTRL_TAB.1);
+    kb.ticks(5);
+    ticks
+}
+
+#[test]
+fn default_term() {
+    assert_eq!(hold_after(&mut Keyboard::new()), TERM);
+}
+
+#[test]
+fn all_terms_are_adjusted() {
+    let mut kb = Keyboard::new();
+
+    settings_key(&mut kb, INCREASE);
+    settings_key(&mut kb, INCREASE);
+    assert_eq!(kb.hold_taps.label().as_deref(), Some("200"));
+    assert_eq!(kb.hold_taps.terms[..2], [200, 200]);
+    assert_eq!(hold_after(&mut kb), 200);
+
+    settings_key(&mut kb, DECREASE);
+    assert_eq!(hold_after(&mut kb), 190);
+}
+
+#[test]
+fn one_term_is_adjusted() {
+    let mut kb = Keyboard::new();
+
+    settings_key(&mut kb, SELECT);
+    assert_eq!(kb.hold_taps.label().as_deref(), Some("C-T"));
+    settings_key(&mut kb, DECREASE);
+    assert_eq!(kb.hold_taps.terms[..2], [170, 200]);
+    assert_eq!(hold_after(&mut kb), 170);
+
+    settings_key(&mut kb, SELECT);
+    assert_eq!(kb.hold_taps.label().as_deref(), Some("A-E"));
+    settings_key(&mut kb, SELECT);
+    assert_eq!(kb.hold_taps.label().as_deref(), Some("All"));
+
+    // the label goes away after a while
+    kb.ticks(2000);
+    assert_eq!(kb.hold_taps.label(), None);
+}
+
+#[test]
+fn tap_within_the_term() {
+    let mut kb = Keyboard::new();
+    settings_key(&mut kb, INCREASE);
+
+    kb.press(CTRL_TAB.0, CTRL_TAB.1);
+    kb.ticks(TERM + 5);
+    assert!(kb.keys.is_empty());
+
+    kb.release(CTRL_TAB.0, CTRL_TAB.1);
+    let mut sent = Vec::new();
+    for _ in 0..5 {
+        kb.tick();
+        sent.extend(kb.keys.iter().copied());
+    }
+    assert!(sent.contains(&Tab) && !sent.contains(&LCtrl));
+}
+
+#[test]
+fn overlapping_hold_taps_keep_their_terms() {
+    let mut kb = Keyboard::new();
+
+    // ALT_ENTER makes CTRL_TAB a hold right away
+    kb.press(CTRL_TAB.0, CTRL_TAB.1);
+    kb.ticks(50);
+    kb.press(ALT_ENTER.0, ALT_ENTER.1);
+    kb.ticks(5);
+    assert!(kb.pressed(LCtrl) && !kb.pressed(LAlt));
+
+    // and the end of the CTRL_TAB term doesn't end its own
+    kb.ticks(150);
+    assert!(!kb.pressed(LAlt));
+    kb.ticks(50);
+    assert!(kb.pressed(LCtrl) && kb.pressed(LAlt));
+}
+
+#[test]
+fn saved_terms_are_checked() {
+    let mut saved = [u16::MAX; MAX_TERMS];
+    saved[0] = 250;
+    saved[1] = 5;
+
+    assert_eq!(HoldTaps::new(saved).terms[..2], [250, 200]);
+}
@@ -0,0 +1,640 @@
+use {
+    keyberon::{key_code::KeyCode::*, layout::CustomEvent},
+    usbd_human_interface_device::page::Consumer,
+    yuki_core::{
+        keyboard::Keyboard,
+        layout::CustomAction,
+        mouse::{Dir, MAction, Speed},
+        restart::Restart,
+    },
+    common::tap,
+};
+
+mod common;
+
+// (3, 8) is CTRL_TAB, (3, 5) ALT_ENTER
+const CTRL_TAB: (u8, u8) = (3, 8);
+const HOLD_TAP_TIMEOUT: usize = 180;
+
+// the settings layer is held with (3) and then (2)
+fn settings_key(kb: &mut Keyboard, coord: (u8, u8)) {
+    kb.press(3, 9);
+    kb.tick();
+    kb.press(3, 2);
+    kb.ticks(2);
+    assert_eq!(kb.layout.current_layer(), 5);
+    tap(kb, coord);
+    kb.release(3, 2);
+    kb.release(3, 9);
+    kb.ticks(3);
+}
+
+#[test]
+fn base_layer_keys() {
+    let mut kb = Keyboard::new();
+
+    kb.press(0, 4);
+    kb.tick();
+    assert_eq!(kb.keys, [P]);
+
+    kb.press(2, 0);
+    kb.tick();
+    assert_eq!(kb.report().as_bytes()[..3], [0x02, 0, P as u8]);
+
+    kb.release(0, 4);
+    kb.release(2, 0);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn thumb_layers() {
+    let mut kb = Keyboard::new();
+
+    // (1) is held, the row 1 gives the numbers
+    kb.press(3, 7);
+    kb.press(1, 1);
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 1);
+    assert_eq!(kb.keys, [Kb1]);
+
+    // releasing the layer key first keeps the number until its release
+    kb.release(3, 7);
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 0);
+    kb.release(1, 1);
+    kb.tick();
+
+    kb.press(1, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [A]);
+}
+
+#[test]
+fn both_thumb_layers_give_the_mouse_layer() {
+    let mut kb = Keyboard::new();
+
+    kb.press(3, 2);
+    kb.press(3, 7);
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 3);
+
+    kb.release(3, 7);
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 2);
+
+    kb.release(3, 2);
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 0);
+}
+
+#[test]
+fn hold_tap_tapped() {
+    let mut kb = Keyboard::new();
+
+    kb.press(CTRL_TAB.0, CTRL_TAB.1);
+    kb.ticks(HOLD_TAP_TIMEOUT / 2);
+    assert!(kb.keys.is_empty());
+
+    kb.release(CTRL_TAB.0, CTRL_TAB.1);
+    let mut sent = Vec::new();
+    for _ in 0..5 {
+        kb.tick();
+        sent.extend(kb.keys.iter().copied());
+    }
+    assert!(sent.contains(&Tab));
+    assert!(!sent.contains(&LCtrl));
+}
+
+#[test]
+fn hold_tap_held_past_timeout() {
+    let mut kb = Keyboard::new();
+
+    kb.press(CTRL_TAB.0, CTRL_TAB.1);
+    kb.ticks(HOLD_TAP_TIMEOUT - 10);
+    assert!(!kb.pressed(LCtrl));
+
+    kb.ticks(20);
+    assert_eq!(kb.keys, [LCtrl]);
+
+    kb.release(CTRL_TAB.0, CTRL_TAB.1);
+    kb.ticks(5);
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn hold_tap_held_on_other_key_press() {
+    let mut kb = Keyboard::new();
+
+    kb.press(CTRL_TAB.0, CTRL_TAB.1);
+    kb.tick();
+    kb.press(0, 4);
+    kb.ticks(5);
+    assert!(kb.pressed(LCtrl) && kb.pressed(P));
+    assert!(!kb.pressed(Tab));
+}
+
+#[test]
+fn default_layer_is_switched() {
+    let mut kb = Keyboard::new();
+
+    // layer 2 (0, 11) is qwerty
+    kb.press(3, 2);
+    kb.tick();
+    tap(&mut kb, (0, 11));
+    kb.release(3, 2);
+    kb.ticks(5);
+    assert_eq!(kb.layout.current_layer(), 4);
+
+    kb.press(0, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [Q]);
+    kb.release(0, 1);
+    kb.tick();
+
+    // and back with layer 2 (3, 4)
+    kb.press(3, 2);
+    kb.tick();
+    tap(&mut kb, (3, 4));
+    kb.release(3, 2);
+    kb.ticks(5);
+    assert_eq!(kb.layout.current_layer(), 0);
+}
+
+#[test]
+fn usb_toggle() {
+    let mut kb = Keyboard::new();
+    assert!(kb.use_right_usb);
+
+    kb.press(3, 2);
+    kb.tick();
+    kb.press(3, 9);
+    assert!(matches!(kb.tick(), CustomEvent::Press(CustomAction::USB)));
+    assert!(!kb.use_right_usb);
+
+    kb.release(3, 9);
+    assert!(matches!(kb.tick(), CustomEvent::Release(CustomAction::USB)));
+    assert!(!kb.use_right_usb);
+}
+
+#[test]
+fn media_keys_use_the_consumer_page() {
+    let mut kb = Keyboard::new();
+
+    // play/pause on layer 1
+    kb.press(3, 7);
+    kb.tick();
+    kb.press(1, 11);
+    kb.ticks(2);
+    assert!(kb.pressed(MediaPlayPause));
+    assert_eq!(kb.keyboard_keys().count(), 0);
+    assert_eq!(kb.consumer_report().codes, [Consumer::PlayPause, Consumer::Unassigned, Consumer::Unassigned, Consumer::Unassigned]);
+    kb.release(1, 11);
+    kb.release(3, 7);
+    kb.ticks(2);
+
+    // volume on the mouse layer, next to a normal key
+    kb.press(3, 9);
+    kb.tick();
+    kb.press(0, 3);
+    kb.press(2, 0);
+    kb.ticks(3);
+    assert_eq!(kb.consumer_report().codes[0], Consumer::VolumeIncrement);
+    assert_eq!(kb.keyboard_keys().collect::<Vec<_>>(), [LShift]);
+    assert_eq!(kb.system_control(), None);
+}
+
+#[test]
+fn nkro_toggle() {
+    let mut kb = Keyboard::new();
+
+    // seven keys of the top row and shift
+    let keys = (1..8).map(|j| (0, j));
+    keys.clone().for_each(|(i, j)| kb.press(i, j));
+    kb.press(2, 0);
+    kb.ticks(10);
+    assert_eq!(kb.keyboard_keys().count(), 8);
+
+    // (1, 4) on the settings layer
+    settings_key(&mut kb, (1, 4));
+    assert!(!kb.nkro);
+
+    // past six keys the boot report only has the rollover error
+    assert_eq!(kb.keyboard_keys().collect::<Vec<_>>(), [LShift, ErrorRollOver, ErrorRollOver, ErrorRollOver, ErrorRollOver, ErrorRollOver, ErrorRollOver]);
+    kb.release(0, 7);
+    kb.ticks(2);
+    assert_eq!(kb.keyboard_keys().collect::<Vec<_>>(), [LShift, Quote, Comma, Dot, P, Y, F]);
+}
+
+#[test]
+fn too_many_keys_are_dropped() {
+    let mut kb = Keyboard::new();
+
+    // the shifted symbols of layer 1 send two codes each, 36 in all
+    kb.press(3, 7);
+    kb.tick();
+    let keys = (1..11).map(|j| (0, j)).chain((1..11).map(|j| (1, j))).chain((4..7).map(|j| (2, j)));
+    keys.for_each(|(i, j)| kb.press(i, j));
+    kb.ticks(30);
+    assert_eq!(kb.keys.len(), 32);
+    assert_eq!(kb.keyboard_keys().count(), 32);
+}
+
+#[test]
+fn mouse_buttons_and_movement() {
+    let mut kb = Keyboard::new();
+
+    // (3) on the base layer (3, 9)
+    kb.press(3, 9);
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 3);
+
+    kb.press(1, 4);
+    assert!(matches!(kb.tick(), CustomEvent::Press(CustomAction::M(MAction::Left))));
+    assert_eq!(kb.mouse.report.buttons, 0x1);
+
+    kb.release(1, 4);
+    assert!(matches!(kb.tick(), CustomEvent::Release(CustomAction::M(MAction::Left))));
+    assert_eq!(kb.mouse.report.buttons, 0);
+
+    kb.press(1, 8);
+    assert!(matches!(kb.tick(), CustomEvent::Press(CustomAction::M(MAction::Move(Dir::Up)))));
+    assert!(kb.mouse.report.y < 0);
+    assert_eq!(kb.mouse.report.x, 0);
+    kb.mouse.sent();
+
+    kb.release(1, 8);
+    kb.tick();
+    assert_eq!(kb.mouse.report.y, 0);
+
+    // no keyboard keys are sent for mouse actions
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn mouse_scroll_repeats() {
+    let mut kb = Keyboard::new();
+
+    kb.press(3, 9);
+    kb.tick();
+    kb.press(2, 4);
+    kb.tick();
+    assert_eq!(kb.mouse.report.vertical_wheel, 1);
+    kb.mouse.sent();
+
+    // the first repeat after the delay, then every interval
+    kb.ticks(299);
+    assert_eq!(kb.mouse.report.vertical_wheel, 0);
+    kb.tick();
+    assert_eq!(kb.mouse.report.vertical_wheel, 1);
+    kb.mouse.sent();
+
+    kb.ticks(90);
+    assert_eq!(kb.mouse.report.vertical_wheel, 0);
+    kb.ticks(20);
+    assert_eq!(kb.mouse.report.vertical_wheel, 1);
+    kb.mouse.sent();
+
+    kb.release(2, 4);
+    kb.ticks(200);
+    assert_eq!(kb.mouse.report.vertical_wheel, 0);
+}
+
+#[test]
+fn mouse_toggle_active() {
+    let mut kb = Keyboard::new();
+    assert!(kb.mouse.active);
+
+    kb.press(3, 9);
+    kb.tick();
+    tap(&mut kb, (0, 0));
+    assert!(!kb.mouse.active);
+}
+
+#[test]
+fn mouse_speed_toggle() {
+    let mut kb = Keyboard::new();
+
+    // (1, 0) on the settings layer toggles precision, it stays on the
+    // other layers
+    settings_key(&mut kb, (1, 0));
+    assert_eq!(kb.layout.current_layer(), 0);
+    assert_eq!(kb.mouse.speed(), Speed::Precision);
+    assert_eq!(kb.info().as_deref(), Some("Pre"));
+
+    // (1, 1) toggles turbo instead
+    settings_key(&mut kb, (1, 1));
+    assert_eq!(kb.mouse.speed(), Speed::Turbo);
+}
+
+#[test]
+fn mouse_layer_keeps_space_and_escape() {
+    let mut kb = Keyboard::new();
+
+    kb.press(3, 9);
+    kb.tick();
+    kb.press(1, 0);
+    kb.press(3, 4);
+    kb.ticks(2);
+    assert_eq!(kb.keys, [Escape, Space]);
+    kb.release(1, 0);
+    kb.release(3, 4);
+    kb.ticks(2);
+
+    // precision while (0, 11) is held
+    kb.press(0, 11);
+    kb.tick();
+    assert_eq!(kb.mouse.speed(), Speed::Precision);
+    kb.release(0, 11);
+    kb.tick();
+    assert_eq!(kb.mouse.speed(), Speed::Normal);
+}
+
+#[test]
+fn key_press_wakes_the_host() {
+    let mut kb = Keyboard::new();
+
+    tap(&mut kb, (1, 1));
+    assert!(!kb.suspend.take_wake());
+
+    assert!(kb.suspend.set(true));
+    assert!(!kb.suspend.set(true));
+    tap(&mut kb, (1, 1));
+    assert!(kb.suspend.take_wake());
+    assert!(!kb.suspend.take_wake());
+
+    // a press that didn't wake it yet is dropped on resume
+    kb.press(1, 2);
+    assert!(kb.suspend.set(false));
+    assert!(!kb.suspend.take_wake());
+}
+
+#[test]
+fn restart_needs_a_long_hold() {
+    let mut kb = Keyboard::new();
+
+    // a short tap of reset does nothing
+    kb.press(3, 9);
+    kb.tick();
+    tap(&mut kb, (2, 11));
+    kb.ticks(1000);
+    assert_eq!(kb.restart, None);
+
+    kb.press(1, 11);
+    kb.ticks(990);
+    assert_eq!(kb.restart, None);
+    assert_eq!(kb.confirm.pending(), Some(Restart::Bootloader));
+    kb.ticks(20);
+    assert_eq!(kb.restart, Some(Restart::Bootloader));
+    assert_eq!(kb.confirm.pending(), None);
+}
+
+#[test]
+fn conditional_layer_on_another_default_layer() {
+    let mut kb = Keyboard::new();
+    kb.set_default_layer(4);
+
+    // the `t` at (3, 2) on layer 1 is read from qwerty now
+    kb.press(3, 7);
+    kb.tick();
+    kb.press(3, 2);
+    kb.ticks(2);
+    assert_eq!(kb.layout.current_layer(), 3);
+}
+
+#[test]
+fn auto_shift() {
+    let mut kb = Keyboard::new();
+
+    // (1, 2) on layer 2 turns it on
+    kb.press(3, 2);
+    kb.tick();
+    tap(&mut kb, (1, 2));
+    kb.release(3, 2);
+    kb.tick();
+    assert!(kb.autoshift.enabled);
+
+    // a tap is sent once released, without shift
+    kb.press(1, 1);
+    kb.ticks(50);
+    assert!(kb.keys.is_empty());
+    kb.release(1, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [A]);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+
+    // held past the timeout it is sent with shift
+    kb.press(1, 1);
+    kb.ticks(170);
+    assert!(kb.keys.is_empty());
+    kb.ticks(10);
+    assert!(kb.pressed(A) && kb.pressed(LShift));
+    kb.release(1, 1);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn shift_backspace_is_delete_on_layer_1() {
+    let mut kb = Keyboard::new();
+
+    kb.press(3, 7);
+    kb.tick();
+    kb.press(2, 0);
+    kb.tick();
+    kb.press(2, 10);
+    kb.tick();
+    assert_eq!(kb.keys, [Delete]);
+
+    kb.release(2, 0);
+    kb.tick();
+    assert_eq!(kb.keys, [BSpace]);
+}
+
+#[test]
+fn conditional_layer_in_either_order() {
+    let mut kb = Keyboard::new();
+
+    // (1) first this time, (2) is `t` on layer 1
+    kb.press(3, 7);
+    kb.tick();
+    kb.press(3, 2);
+    kb.ticks(2);
+    assert_eq!(kb.layout.current_layer(), 3);
+
+    // the left click of the mouse layer
+    kb.press(1, 4);
+    kb.tick();
+    assert_eq!(kb.mouse.report.buttons, 0x1);
+    kb.release(1, 4);
+    kb.tick();
+
+    // releasing either one ends it
+    kb.release(3, 2);
+    kb.ticks(2);
+    assert_eq!(kb.layout.current_layer(), 1);
+}
+
+#[test]
+fn repeat_key() {
+    let mut kb = Keyboard::new();
+
+    // shift + a, the modifiers are repeated too
+    kb.press(2, 0);
+    kb.tick();
+    tap(&mut kb, (1, 1));
+    kb.release(2, 0);
+    kb.tick();
+
+    // (3, 9) on layer 1
+    kb.press(3, 7);
+    kb.tick();
+    kb.press(3, 9);
+    kb.tick();
+    assert_eq!(kb.keys, [LShift, A]);
+    kb.release(3, 9);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn alt_repeat_key() {
+    let mut kb = Keyboard::new();
+
+    // up on layer 2
+    kb.press(3, 2);
+    kb.tick();
+    tap(&mut kb, (1, 8));
+    kb.release(3, 2);
+    kb.tick();
+
+    // (1, 0) on layer 1 sends the counterpart
+    kb.press(3, 7);
+    kb.tick();
+    kb.press(1, 0);
+    kb.tick();
+    assert_eq!(kb.keys, [Down]);
+    kb.release(1, 0);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn swap_hands_toggle_and_one_shot() {
+    let mut kb = Keyboard::new();
+
+    // (1, 1) on the mouse layer toggles it
+    kb.press(3, 9);
+    kb.tick();
+    tap(&mut kb, (1, 1));
+    kb.release(3, 9);
+    kb.tick();
+    assert!(kb.swap.is_active());
+
+    kb.press(1, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [S]);
+    kb.release(1, 1);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+
+    kb.press(3, 9);
+    kb.tick();
+    tap(&mut kb, (1, 1));
+    assert!(!kb.swap.is_active());
+
+    // (2, 2) only swaps the next key
+    tap(&mut kb, (2, 2));
+    kb.release(3, 9);
+    kb.tick();
+
+    kb.press(1, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [S]);
+    kb.release(1, 1);
+    kb.tick();
+
+    kb.press(1, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [A]);
+}
+
+#[test]
+fn swap_hands_momentary() {
+    let mut kb = Keyboard::new();
+
+    // (2, 1) on the mouse layer, held
+    kb.press(3, 9);
+    kb.tick();
+    kb.press(2, 1);
+    kb.tick();
+    kb.release(3, 9);
+    kb.tick();
+
+    kb.press(1, 2);
+    kb.tick();
+    assert_eq!(kb.keys, [N]);
+
+    // a key pressed swapped is released swapped
+    kb.release(2, 1);
+    kb.tick();
+    assert!(!kb.swap.is_active());
+    kb.release(1, 2);
+    kb.tick();
+    assert!(kb.keys.is_empty());
+
+    kb.press(1, 2);
+    kb.tick();
+    assert_eq!(kb.keys, [O]);
+}
+
+#[test]
+fn shortcuts_follow_the_host() {
+    let mut kb = Keyboard::new();
+
+    // nothing was detected without a usb bus, undo ((2, 1) on layer 2) uses ctrl
+    assert_eq!(kb.os.host().name(), "?");
+    kb.press(3, 2);
+    kb.tick();
+    kb.press(2, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [LCtrl, Z]);
+    kb.release(2, 1);
+    kb.release(3, 2);
+    kb.ticks(2);
+    assert!(kb.keys.is_empty());
+
+    // (0, 11) on the settings layer cycles linux, windows, macos and back to detected
+    settings_key(&mut kb, (0, 11));
+    assert_eq!(kb.os.host().name(), "LNX");
+    settings_key(&mut kb, (0, 11));
+    settings_key(&mut kb, (0, 11));
+    assert_eq!(kb.os.host().name(), "MAC");
+
+    kb.press(3, 2);
+    kb.tick();
+    kb.press(2, 1);
+    kb.tick();
+    assert_eq!(kb.keys, [LGui, Z]);
+    kb.release(2, 1);
+    kb.release(3, 2);
+    kb.ticks(2);
+
+    settings_key(&mut kb, (0, 11));
+    assert_eq!(kb.os.host().name(), "?");
+}
+
+#[test]
+fn keys_of_a_lost_half_are_released() {
+    let mut kb = Keyboard::new();
+
+    kb.press(0, 4);
+    kb.press(0, 8);
+    kb.tick();
+    assert_eq!(kb.keys, [P, C]);
+
+    kb.release_cols(6..12);
+    kb.tick();
+    assert_eq!(kb.keys, [P]);
+}
@@ -0,0 +1,40 @@
+use {
+    keyberon::layout::Event,
+    yuki_core::link::{de, ser, take_motion, Msg},
+};
+
+#[test]
+fn every_message_round_trips() {
+    let events = (0..4).flat_map(|i| (0..6).flat_map(move |j| [Event::Press(i, j), Event::Release(i, j)]));
+    let msgs = events.map(Msg::Event).chain((0..16).map(Msg::DefaultLayer))
+        .chain([Msg::Bootloader, Msg::Suspend(true), Msg::Suspend(false)])
+        .chain((-7..=7).flat_map(|d| [Msg::MotionX(d), Msg::MotionY(d)]));
+
+    for msg in msgs {
+        assert_eq!(de(ser(msg)), Ok(msg));
+    }
+}
+
+#[test]
+fn wire_format() {
+    assert_eq!(ser(Msg::Event(Event::Press(2, 5))), 0b1010_1010);
+    assert_eq!(ser(Msg::Event(Event::Release(2, 5))), 0b0010_1010);
+    assert_eq!(ser(Msg::DefaultLayer(4)), 0b0100_0100);
+    assert_eq!(ser(Msg::Bootloader), 0b0101_0000);
+    assert_eq!(ser(Msg::Suspend(true)), 0b0101_0001);
+    assert_eq!(ser(Msg::MotionY(-2)), 0b0111_1110);
+}
+
+#[test]
+fn unknown_messages_are_rejected() {
+    assert!((0b1100_0000..=0b1111_1111).all(|n| de(n).is_err()));
+    assert!((0b0101_0011..=0b0101_1111).all(|n| de(n).is_err()));
+}
+
+#[test]
+fn motion_is_split_over_messages() {
+    let mut pending = -20;
+    let sent: Vec<i8> = (0..4).map(|_| take_motion(&mut pending)).collect();
+    assert_eq!(sent, [-7, -7, -6, 0]);
+    assert_eq!(pending, 0);
+}
@@ -0,0 +1,277 @@
+//! Checks on the keymap that keyberon can't do at compile time, each test
+//! fails with the list of the keys at fault.
+
+use {
+    keyberon::action::Action,
+    yuki_core::{
+        keyboard::{NUM_COLS, NUM_LAYERS, NUM_ROWS},
+        layout::{CustomAction, AUTO_MOUSE_COORD, CONDITIONAL_LAYERS, LAYERS},
+    },
+};
+
+type Layer = [[Action<CustomAction>; NUM_COLS]; NUM_ROWS];
+
+// the thumb row and its keys that have no switch, the virtual keys
+// of the conditional layers and the auto mouse layer are put on those
+const THUMB_ROW: usize = 3;
+const NO_SWITCH: &[usize] = &[0, 1, 10, 11];
+
+fn check(errors: Vec<String>) {
+    assert!(errors.is_empty(), "\n{}\n", errors.join("\n"));
+}
+
+/// Layers the action switches to, momentarily or as the default layer.
+fn targets(action: &Action<CustomAction>, momentary: &mut Vec<usize>, default: &mut Vec<usize>) {
+    match action {
+        Action::Layer(n) => momentary.push(*n),
+        Action::DefaultLayer(n) => default.push(*n),
+        Action::Custom(CustomAction::DefaultLayer(n)) => default.push(*n as usize),
+        Action::HoldTap(ht) => {
+            targets(&ht.hold, momentary, default);
+            targets(&ht.tap, momentary, default);
+        },
+        Action::MultipleActions(actions) => actions.iter().for_each(|a| targets(a, momentary, default)),
+        _ => (),
+    }
+}
+
+fn keys(layers: &[Layer]) -> impl Iterator<Item = (usize, usize, usize, &Action<CustomAction>)> {
+    layers.iter().enumerate().flat_map(|(l, layer)| {
+        layer.iter().enumerate().flat_map(move |(i, row)| row.iter().enumerate().map(move |(j, a)| (l, i, j, a)))
+    })
+}
+
+/// Layers active while `default` is the default layer, by holding keys.
+fn momentary_closure(layers: &[Layer], default: usize) -> Vec<usize> {
+    let mut reached = vec![default];
+    let mut i = 0;
+
+    while let Some(&layer) = reached.get(i) {
+        let mut momentary = Vec::new();
+        layers.get(layer).into_iter().flatten().flatten().for_each(|a| targets(a, &mut momentary, &mut Vec::new()));
+
+        // all the layers of a condition held give its layer
+        let conditional: Vec<usize> = CONDITIONAL_LAYERS.iter()
+            .filter(|c| c.when.iter().all(|w| reached.contains(w) || momentary.contains(w)))
+            .map(|c| c.then)
+            .collect();
+        momentary.extend(conditional);
+
+        momentary.into_iter().for_each(|m| if !reached.contains(&m) { reached.push(m) });
+        i += 1;
+    }
+
+    reached
+}
+
+/// Default layers that can be set while `default` is the default layer.
+fn default_targets(layers: &[Layer], default: usize) -> Vec<usize> {
+    let mut defaults = Vec::new();
+    for l in momentary_closure(layers, default) {
+        layers.get(l).into_iter().flatten().flatten().for_each(|a| targets(a, &mut Vec::new(), &mut defaults));
+    }
+    defaults
+}
+
+/// Every layer that can become the default one, starting from layer 0.
+fn default_layers(layers: &[Layer]) -> Vec<usize> {
+    let mut reached = vec![0];
+    let mut i = 0;
+
+    while let Some(&layer) = reached.get(i) {
+        for d in default_targets(layers, layer) {
+            if !reached.contains(&d) && d < layers.len() {
+                reached.push(d);
+            }
+        }
+        i += 1;
+    }
+
+    reached
+}
+
+fn transparent_default_layers(layers: &[Layer]) -> Vec<String> {
+    let defaults = default_layers(layers);
+
+    keys(layers)
+        .filter(|(l, _, _, a)| defaults.contains(l) && matches!(a, Action::Trans))
+        .map(|(l, i, j, _)| format!("layer {} ({}, {}): `t` on a layer that can be the default one does nothing", l, i, j))
+        .collect()
+}
+
+fn layers_out_of_range(layers: &[Layer]) -> Vec<String> {
+    let mut errors: Vec<String> = keys(layers)
+        .flat_map(|(l, i, j, a)| {
+            let (mut momentary, mut default) = (Vec::new(), Vec::new());
+            targets(a, &mut momentary, &mut default);
+
+            momentary.into_iter().chain(default)
+                .filter(|n| *n >= layers.len())
+                .map(move |n| format!("layer {} ({}, {}): layer {} does not exist, there are {}", l, i, j, n, layers.len()))
+        })
+        .collect();
+
+    for c in CONDITIONAL_LAYERS {
+        for n in c.when.iter().chain([&c.then]).filter(|n| **n >= layers.len()) {
+            errors.push(format!("conditional layer {:?} -> {}: layer {} does not exist", c.when, c.then, n));
+        }
+    }
+
+    errors
+}
+
+/// The virtual key of a conditional layer is what holds it, so it has to
+/// hold `then` in every one of the `when` layers.
+fn conditional_keys_disagree(layers: &[Layer]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for c in CONDITIONAL_LAYERS {
+        let (i, j) = (c.coord.0 as usize, c.coord.1 as usize);
+        for &w in c.when.iter().filter(|w| **w < layers.len()) {
+            if !matches!(layers[w][i][j], Action::Layer(n) if n == c.then) {
+                errors.push(format!("layer {} ({}, {}): should be `({})` for the conditional layer {:?} -> {}", w, i, j, c.then, c.when, c.then));
+            }
+        }
+    }
+
+    errors
+}
+
+fn unreachable_layers(layers: &[Layer]) -> Vec<String> {
+    let reached: Vec<usize> = default_layers(layers)
+        .into_iter()
+        .flat_map(|d| momentary_closure(layers, d))
+        .collect();
+
+    (0..layers.len())
+        .filter(|l| !reached.contains(l))
+        .map(|l| format!("layer {}: can not be reached from layer 0", l))
+        .collect()
+}
+
+fn no_way_back(layers: &[Layer]) -> Vec<String> {
+    default_layers(layers)
+        .into_iter()
+        .filter(|&d| {
+            // layers that can be made default from here, and from those..
+            let mut reached = vec![d];
+            let mut i = 0;
+            while let Some(&layer) = reached.get(i) {
+                for t in default_targets(layers, layer) {
+                    if !reached.contains(&t) && t < layers.len() {
+                        reached.push(t);
+                    }
+                }
+                i += 1;
+            }
+            !reached.contains(&0)
+        })
+        .map(|d| format!("layer {}: once it is the default layer there is no key setting layer 0 back", d))
+        .collect()
+}
+
+fn inconsistent_thumb_rows(layers: &[Layer]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let virtual_keys: Vec<(usize, usize)> = CONDITIONAL_LAYERS.iter()
+        .map(|c| c.coord)
+        .chain([AUTO_MOUSE_COORD])
+        .map(|(i, j)| (i as usize, j as usize))
+        .collect();
+
+    let same = |a: &Action<CustomAction>, b: &Action<CustomAction>| match (a, b) {
+        (Action::Layer(n), Action::Layer(m)) => n == m,
+        (Action::HoldTap(h), Action::HoldTap(g)) => core::ptr::eq(*h, *g),
+        _ => false,
+    };
+    let base = &layers[0][THUMB_ROW];
+
+    for (l, layer) in layers.iter().enumerate() {
+        for (j, a) in layer[THUMB_ROW].iter().enumerate() {
+            if NO_SWITCH.contains(&j) {
+                if !matches!(a, Action::NoOp) && !virtual_keys.contains(&(THUMB_ROW, j)) {
+                    errors.push(format!("layer {} ({}, {}): there is no switch for this key, it should be `n`", l, THUMB_ROW, j));
+                }
+                continue;
+            }
+
+            // layer keys and hold-taps of the base layer don't move around
+            if let Some(c) = base.iter().position(|b| same(a, b)).filter(|c| *c != j) {
+                errors.push(format!("layer {} ({}, {}): is at ({}, {}) on layer 0", l, THUMB_ROW, j, THUMB_ROW, c));
+            }
+
+            // and the hold-taps are on every layer
+            if matches!(base[j], Action::HoldTap(_)) && !same(a, &base[j]) && !matches!(a, Action::Trans) {
+                errors.push(format!("layer {} ({}, {}): the hold-tap of layer 0 is missing", l, THUMB_ROW, j));
+            }
+        }
+    }
+
+    errors
+}
+
+#[test]
+fn no_transparent_keys_on_default_layers() {
+    check(transparent_default_layers(&LAYERS));
+}
+
+#[test]
+fn layers_in_range() {
+    assert_eq!(LAYERS.len(), NUM_LAYERS);
+    check(layers_out_of_range(&LAYERS));
+}
+
+#[test]
+fn conditional_layer_keys() {
+    check(conditional_keys_disagree(&LAYERS));
+}
+
+#[test]
+fn every_layer_is_reachable() {
+    check(unreachable_layers(&LAYERS));
+}
+
+#[test]
+fn way_back_to_layer_0() {
+    check(no_way_back(&LAYERS));
+}
+
+#[test]
+fn consistent_thumb_rows() {
+    check(inconsistent_thumb_rows(&LAYERS));
+}
+
+#[test]
+fn broken_keymap_is_reported() {
+    use keyberon::{action::k, key_code::KeyCode::*};
+
+    // layer 1 is only reachable through a layer that does not exist, layer 2
+    // is the default one after (3, 5) with a `t`, a moved layer key and no way back
+    const NOOP: Action<CustomAction> = Action::NoOp;
+    const ROW: [Action<CustomAction>; NUM_COLS] = [NOOP; NUM_COLS];
+    const EMPTY: Layer = [ROW; NUM_ROWS];
+
+    let mut layers = [EMPTY; 3];
+    layers[0][THUMB_ROW][5] = Action::DefaultLayer(2);
+    layers[0][THUMB_ROW][7] = Action::Layer(7);
+    layers[2][0][0] = Action::Trans;
+    layers[2][THUMB_ROW][5] = k(Space);
+    layers[2][THUMB_ROW][6] = Action::Layer(7);
+
+    assert_eq!(transparent_default_layers(&layers), ["layer 2 (0, 0): `t` on a layer that can be the default one does nothing"]);
+    assert_eq!(layers_out_of_range(&layers), [
+        "layer 0 (3, 7): layer 7 does not exist, there are 3",
+        "layer 2 (3, 6): layer 7 does not exist, there are 3",
+        "conditional layer [1, 2] -> 3: layer 3 does not exist",
+        "conditional layer [2, 3] -> 5: layer 3 does not exist",
+        "conditional layer [2, 3] -> 5: layer 5 does not exist",
+    ]);
+    assert_eq!(unreachable_layers(&layers), ["layer 1: can not be reached from layer 0"]);
+    assert_eq!(no_way_back(&layers), ["layer 2: once it is the default layer there is no key setting layer 0 back"]);
+    assert_eq!(inconsistent_thumb_rows(&layers), ["layer 2 (3, 6): is at (3, 7) on layer 0"]);
+    // the virtual keys of the mouse and settings layers are missing
+    assert_eq!(conditional_keys_disagree(&layers), [
+        "layer 1 (3, 0): should be `(3)` for the conditional layer [1, 2] -> 3",
+        "layer 2 (3, 0): should be `(3)` for the conditional layer [1, 2] -> 3",
+        "layer 2 (3, 11): should be `(5)` for the conditional layer [2, 3] -> 5",
+    ]);
+}
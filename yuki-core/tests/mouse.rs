@@ -0,0 +1,236 @@
+use yuki_core::mouse::{Dir, MAction, Motion, Mouse, Profile, Speed, SpeedModes, Wheel};
+
+fn mouse(profile: Profile) -> Mouse {
+    let mut m = Mouse::new();
+    m.motion = Motion { profile, start_speed: 100, max_speed: 1000, time_to_max: 100 };
+    m
+}
+
+// pixels moved over `ticks`
+fn run(m: &mut Mouse, ticks: usize) -> (i32, i32) {
+    (0..ticks).fold((0, 0), |(x, y), _| {
+        m.mouse_tick();
+        let moved = (x + m.report.x as i32, y + m.report.y as i32);
+        m.sent();
+        moved
+    })
+}
+
+#[test]
+fn tap_moves_one_pixel() {
+    let mut m = mouse(Profile::Linear);
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    assert_eq!(run(&mut m, 1), (1, 0));
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), false);
+    assert_eq!(run(&mut m, 10), (0, 0));
+}
+
+#[test]
+fn constant_speed() {
+    let mut m = mouse(Profile::Constant);
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Down), true);
+    run(&mut m, 1);
+    // 1000 px/s
+    assert_eq!(run(&mut m, 100), (0, 100));
+    assert_eq!(run(&mut m, 100), (0, 100));
+}
+
+#[test]
+fn linear_ramp_reaches_max_speed() {
+    let mut m = mouse(Profile::Linear);
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Left), true);
+    let (ramp, _) = run(&mut m, 100);
+    assert!(-ramp > 50 && -ramp < 70, "{}", ramp);
+
+    assert_eq!(run(&mut m, 100), (-100, 0));
+}
+
+#[test]
+fn kinetic_starts_slower_than_linear() {
+    let (mut k, mut l) = (mouse(Profile::Kinetic), mouse(Profile::Linear));
+    k.handle_mouse_btn(&MAction::Move(Dir::Up), true);
+    l.handle_mouse_btn(&MAction::Move(Dir::Up), true);
+
+    let (kin, lin) = (run(&mut k, 50).1, run(&mut l, 50).1);
+    assert!(kin > lin, "{} {}", kin, lin);
+
+    // both at max speed in the end
+    run(&mut k, 50);
+    run(&mut l, 50);
+    assert_eq!(run(&mut k, 100), run(&mut l, 100));
+}
+
+#[test]
+fn inertia_keeps_gliding() {
+    let mut m = mouse(Profile::Inertia);
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    run(&mut m, 200);
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), false);
+
+    let (glide, _) = run(&mut m, 100);
+    assert!(glide > 40, "{}", glide);
+    assert_eq!(run(&mut m, 10), (0, 0));
+}
+
+#[test]
+fn diagonals_are_not_faster() {
+    let mut m = mouse(Profile::Constant);
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Up), true);
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    run(&mut m, 1);
+    let (x, y) = run(&mut m, 1000);
+    assert!((700..=708).contains(&x), "{}", x);
+    assert_eq!(x, -y);
+}
+
+#[test]
+fn speedup() {
+    let mut m = mouse(Profile::Constant);
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    m.handle_mouse_btn(&MAction::Speedup, true);
+    run(&mut m, 1);
+    assert_eq!(run(&mut m, 100), (250, 0));
+}
+
+#[test]
+fn precision_mode() {
+    let mut m = mouse(Profile::Constant);
+    m.speeds = SpeedModes { precision: 50, turbo: 300 };
+
+    m.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    m.handle_mouse_btn(&MAction::Precision, true);
+    run(&mut m, 1);
+    assert_eq!(run(&mut m, 100), (50, 0));
+}
+
+#[test]
+fn speed_mode_order() {
+    let mut m = Mouse::new();
+    m.layer_speed = Speed::Turbo;
+    assert_eq!(m.speed(), Speed::Turbo);
+
+    // toggled over the layer one
+    m.handle_mouse_btn(&MAction::ToggleSpeed(Speed::Precision), true);
+    m.handle_mouse_btn(&MAction::ToggleSpeed(Speed::Precision), false);
+    assert_eq!(m.speed(), Speed::Precision);
+
+    // held over the toggled one
+    m.handle_mouse_btn(&MAction::Speedup, true);
+    assert_eq!(m.speed(), Speed::Turbo);
+    m.handle_mouse_btn(&MAction::Speedup, false);
+
+    // toggled again goes back to the layer one
+    m.handle_mouse_btn(&MAction::ToggleSpeed(Speed::Precision), true);
+    assert_eq!(m.speed(), Speed::Turbo);
+    assert_eq!(m.speed_label().as_deref(), Some("Tur"));
+
+    m.layer_speed = Speed::Normal;
+    assert_eq!(m.speed_label(), None);
+}
+
+fn wheel(m: &mut Mouse, ticks: usize) -> Vec<i8> {
+    (0..ticks).map(|_| {
+        m.mouse_tick();
+        let w = m.report.vertical_wheel;
+        m.sent();
+        w
+    }).collect()
+}
+
+#[test]
+fn scroll_speeds_up() {
+    let mut m = Mouse::new();
+    m.wheel = Wheel { delay: 100, interval: 100, min_interval: 20, accel: 20 };
+
+    m.handle_mouse_btn(&MAction::Scroll(Dir::Down), true);
+    assert_eq!(m.report.vertical_wheel, -1);
+    m.sent();
+
+    let first: i32 = wheel(&mut m, 1000).iter().map(|&w| w as i32).sum();
+    let second: i32 = wheel(&mut m, 1000).iter().map(|&w| w as i32).sum();
+    assert!(second < first, "{} {}", first, second);
+    // at the min interval
+    assert_eq!(second, -50);
+
+    m.handle_mouse_btn(&MAction::Scroll(Dir::Down), false);
+    assert!(wheel(&mut m, 100).iter().all(|&w| w == 0));
+}
+
+#[test]
+fn turbo_scrolls_faster() {
+    let mut m = Mouse::new();
+    m.wheel = Wheel { delay: 100, interval: 100, min_interval: 100, accel: 0 };
+    m.speeds = SpeedModes { precision: 50, turbo: 200 };
+
+    m.handle_mouse_btn(&MAction::Speedup, true);
+    m.handle_mouse_btn(&MAction::Scroll(Dir::Up), true);
+    assert_eq!(m.report.vertical_wheel, 2);
+    m.sent();
+
+    // a notch at the delay, then one per interval, all doubled
+    let sum: i32 = wheel(&mut m, 1000).iter().map(|&w| w as i32).sum();
+    assert!((19..=20).contains(&sum), "{}", sum);
+}
+
+#[test]
+fn hires_scroll_is_smooth() {
+    let mut m = Mouse::new();
+    m.wheel = Wheel { delay: 100, interval: 100, min_interval: 100, accel: 0 };
+    m.wheel_multiplier = 16;
+
+    m.handle_mouse_btn(&MAction::Scroll(Dir::Up), true);
+    assert_eq!(m.report.vertical_wheel, 16);
+    m.sent();
+
+    let delay = wheel(&mut m, 100);
+    assert_eq!(delay.iter().map(|&w| w as i32).sum::<i32>(), 16);
+
+    // a notch spread over the interval instead of all at once
+    let repeat = wheel(&mut m, 1000);
+    assert!(repeat.iter().all(|&w| w <= 1));
+    assert!((159..=160).contains(&repeat.iter().map(|&w| w as i32).sum::<i32>()));
+}
+
+#[test]
+fn back_and_forward() {
+    let mut m = Mouse::new();
+
+    m.handle_mouse_btn(&MAction::Back, true);
+    assert_eq!(m.report.buttons, 0x8);
+    m.handle_mouse_btn(&MAction::Forward, true);
+    assert_eq!(m.report.buttons, 0x18);
+    m.handle_mouse_btn(&MAction::Back, false);
+    m.handle_mouse_btn(&MAction::Forward, false);
+    assert_eq!(m.report.buttons, 0);
+}
+
+#[test]
+fn drag_lock() {
+    let mut m = Mouse::new();
+
+    m.handle_mouse_btn(&MAction::DragLock, true);
+    m.handle_mouse_btn(&MAction::DragLock, false);
+    assert_eq!(m.report.buttons, 0x1);
+
+    // other buttons keep the drag
+    m.handle_mouse_btn(&MAction::Right, true);
+    m.handle_mouse_btn(&MAction::Right, false);
+    assert_eq!(m.report.buttons, 0x1);
+
+    // pressing it again drops
+    m.handle_mouse_btn(&MAction::DragLock, true);
+    assert_eq!(m.report.buttons, 0);
+
+    // so does a click
+    m.handle_mouse_btn(&MAction::DragLock, true);
+    m.handle_mouse_btn(&MAction::Left, true);
+    assert_eq!(m.report.buttons, 0x1);
+    m.handle_mouse_btn(&MAction::Left, false);
+    assert_eq!(m.report.buttons, 0);
+}
@@ -0,0 +1,43 @@
+use {
+    embedded_graphics::{mock_display::MockDisplay, pixelcolor::BinaryColor, prelude::*},
+    yuki_core::{oled::Screen, os::HostOs},
+};
+
+fn display() -> MockDisplay<BinaryColor> {
+    let mut display = MockDisplay::new();
+    // the text is drawn over the image
+    display.set_allow_overdraw(true);
+    display.set_allow_out_of_bounds_drawing(true);
+    display
+}
+
+#[test]
+fn redraws_only_on_change() {
+    let mut screen = Screen::new();
+    let mut d = display();
+
+    assert_eq!(screen.draw(&mut d, 0, true, HostOs::Linux, None), Ok(true));
+    assert_eq!(screen.draw(&mut d, 0, true, HostOs::Linux, None), Ok(false));
+    assert_eq!(screen.draw(&mut d, 1, true, HostOs::Linux, None), Ok(true));
+    assert_eq!(screen.draw(&mut d, 1, false, HostOs::Linux, None), Ok(true));
+    assert_eq!(screen.draw(&mut d, 1, false, HostOs::MacOs, None), Ok(true));
+    assert_eq!(screen.draw(&mut d, 1, false, HostOs::MacOs, Some("180")), Ok(true));
+    assert_eq!(screen.draw(&mut d, 1, false, HostOs::MacOs, Some("180")), Ok(false));
+}
+
+#[test]
+fn layer_is_shown() {
+    let draw = |layer| {
+        let mut d = display();
+        Screen::new().draw(&mut d, layer, true, HostOs::Unknown, None).unwrap();
+        d
+    };
+
+    let (a, b) = (draw(0), draw(3));
+    let text = |d: &MockDisplay<BinaryColor>, p| d.get_pixel(p);
+
+    // the "L n" line starts at y 55, the mock display ends at 64
+    let differs = (55..64).flat_map(|y| (0..32).map(move |x| Point::new(x, y)))
+        .any(|p| text(&a, p) != text(&b, p));
+    assert!(differs);
+}
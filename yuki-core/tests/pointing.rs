@@ -0,0 +1,139 @@
+use {
+    std::collections::VecDeque,
+    yuki_core::{
+        keyboard::Keyboard,
+        layout::POINTING,
+        link::take_motion,
+        mouse::{Speed, SpeedModes},
+        pointing::Sensor,
+    },
+    common::tap,
+};
+
+mod common;
+
+/// Plays back recorded motion, then stays still.
+struct MockSensor(VecDeque<(i16, i16)>);
+
+impl Sensor for MockSensor {
+    fn motion(&mut self) -> Result<(i16, i16), ()> {
+        Ok(self.0.pop_front().unwrap_or((0, 0)))
+    }
+}
+
+fn sensor(moves: &[(i16, i16)]) -> MockSensor {
+    MockSensor(moves.iter().copied().collect())
+}
+
+// runs the sensor for `ticks`, returns the pointer movement
+fn run(kb: &mut Keyboard, sensor: &mut impl Sensor, ticks: usize) -> (i32, i32) {
+    (0..ticks).fold((0, 0), |(x, y), _| {
+        kb.motion(sensor.motion().unwrap());
+        kb.tick();
+        let moved = (x + kb.mouse.report.x as i32, y + kb.mouse.report.y as i32);
+        kb.mouse.sent();
+        moved
+    })
+}
+
+#[test]
+fn motion_moves_the_pointer() {
+    let mut kb = Keyboard::new();
+    assert_eq!(POINTING.cpi, POINTING.sensor_cpi);
+
+    let mut s = sensor(&[(10, -5), (3, 0), (-1, 2)]);
+    assert_eq!(run(&mut kb, &mut s, 5), (12, -3));
+}
+
+#[test]
+fn sniping_is_slower() {
+    let mut kb = Keyboard::new();
+
+    // (1, 9) on the mouse layer
+    kb.press(3, 9);
+    kb.press(1, 9);
+    kb.ticks(2);
+
+    let ratio = (POINTING.sensor_cpi / POINTING.sniping_cpi) as i16;
+    let mut s = sensor(&[(ratio * 10, 0), (ratio / 2, 0), (ratio / 2, 0)]);
+    assert_eq!(run(&mut kb, &mut s, 3), (11, 0));
+}
+
+#[test]
+fn speed_modes_scale_the_sensor() {
+    let mut kb = Keyboard::new();
+    kb.mouse.speeds = SpeedModes { precision: 25, turbo: 200 };
+
+    kb.mouse.toggled_speed = Speed::Precision;
+    assert_eq!(run(&mut kb, &mut sensor(&[(40, -8)]), 1), (10, -2));
+
+    kb.mouse.toggled_speed = Speed::Turbo;
+    assert_eq!(run(&mut kb, &mut sensor(&[(40, -8)]), 1), (80, -16));
+}
+
+#[test]
+fn drag_scroll() {
+    let mut kb = Keyboard::new();
+
+    // (0, 6) on the mouse layer
+    kb.press(3, 9);
+    kb.press(0, 6);
+    kb.ticks(2);
+
+    let notch = POINTING.drag_scroll_counts as i16;
+    kb.motion((0, -2 * notch - 1));
+    kb.motion((notch, 0));
+    assert_eq!((kb.mouse.report.x, kb.mouse.report.y), (0, 0));
+    assert_eq!(kb.mouse.report.vertical_wheel, 2);
+    assert_eq!(kb.mouse.report.horizontal_wheel, 1);
+}
+
+#[test]
+fn auto_mouse_layer() {
+    let mut kb = Keyboard::new();
+    let timeout = POINTING.auto_mouse_timeout as usize;
+
+    kb.motion((1, 1));
+    kb.tick();
+    assert_eq!(kb.layout.current_layer(), 3);
+    assert!(kb.pointing.auto_mouse_active());
+
+    // mouse keys keep it, (1, 4) is the left click
+    kb.ticks(timeout - 10);
+    tap(&mut kb, (1, 4));
+    kb.ticks(timeout - 20);
+    assert_eq!(kb.layout.current_layer(), 3);
+
+    kb.ticks(20);
+    assert_eq!(kb.layout.current_layer(), 0);
+
+    // keys type normally again, (0, 4) is P
+    kb.press(0, 4);
+    kb.tick();
+    assert!(!kb.keys.is_empty());
+}
+
+#[test]
+fn motion_over_the_link() {
+    // the sensor half sends what fits each tick, the other half gets
+    // the same motion a few ticks later
+    let (mut sender, mut receiver) = (Keyboard::new(), Keyboard::new());
+    let mut pending = (30, -12);
+    let (mut sent, mut received) = ((0, 0), (0, 0));
+
+    for _ in 0..10 {
+        let d = (take_motion(&mut pending.0) as i16, take_motion(&mut pending.1) as i16);
+        sender.motion(d);
+        receiver.motion(d);
+
+        for (kb, total) in [(&mut sender, &mut sent), (&mut receiver, &mut received)] {
+            kb.tick();
+            *total = (total.0 + kb.mouse.report.x as i32, total.1 + kb.mouse.report.y as i32);
+            kb.mouse.sent();
+        }
+    }
+
+    assert_eq!(sent, (30, -12));
+    assert_eq!(received, sent);
+    assert_eq!(sender.layout.current_layer(), receiver.layout.current_layer());
+}
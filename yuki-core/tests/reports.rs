@@ -0,0 +1,105 @@
+use {
+    keyberon::key_code::KeyCode::{self, *},
+    yuki_core::{
+        keyboard::Keyboard,
+        mouse::{Dir, MAction},
+        reports::{Report, Reports, MOTION_INTERVAL},
+    },
+};
+
+#[derive(Debug, PartialEq)]
+enum Sent {
+    Keys(Vec<KeyCode>),
+    Mouse(u8, i32),
+}
+
+// ticks the keyboard with a host that doesn't take any report, then
+// takes all the queued ones
+fn run(kb: &mut Keyboard, reports: &mut Reports, ticks: usize) {
+    for _ in 0..ticks {
+        kb.tick();
+        reports.update(kb);
+        reports.send(|_| false);
+    }
+}
+
+fn take(reports: &mut Reports) -> Vec<Sent> {
+    let mut sent = Vec::new();
+    reports.send(|r| {
+        match r {
+            Report::Keyboard(keys) => sent.push(Sent::Keys(keys.to_vec())),
+            Report::Mouse(m) => sent.push(Sent::Mouse(m.buttons, m.x as i32)),
+            _ => (),
+        }
+        true
+    });
+    sent
+}
+
+#[test]
+fn short_tap_keeps_both_reports() {
+    let (mut kb, mut reports) = (Keyboard::new(), Reports::new());
+
+    kb.press(1, 1);
+    run(&mut kb, &mut reports, 2);
+    kb.release(1, 1);
+    run(&mut kb, &mut reports, 2);
+
+    assert_eq!(take(&mut reports), [Sent::Keys(vec![A]), Sent::Keys(vec![])]);
+}
+
+#[test]
+fn nothing_is_sent_without_changes() {
+    let (mut kb, mut reports) = (Keyboard::new(), Reports::new());
+
+    run(&mut kb, &mut reports, 100);
+    assert!(reports.is_empty());
+}
+
+#[test]
+fn shift_click_stays_in_order() {
+    let (mut kb, mut reports) = (Keyboard::new(), Reports::new());
+
+    // shift and the left click on the mouse layer
+    kb.press(3, 9);
+    run(&mut kb, &mut reports, 2);
+    kb.press(2, 0);
+    run(&mut kb, &mut reports, 2);
+    kb.press(1, 4);
+    run(&mut kb, &mut reports, 2);
+
+    assert_eq!(take(&mut reports), [Sent::Keys(vec![LShift]), Sent::Mouse(1, 0)]);
+}
+
+#[test]
+fn motion_is_sent_once_per_interval() {
+    let (mut kb, mut reports) = (Keyboard::new(), Reports::new());
+
+    kb.mouse.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    let mut sent = Vec::new();
+    for _ in 0..MOTION_INTERVAL * 5 {
+        kb.tick();
+        reports.update(&mut kb);
+        sent.extend(take(&mut reports));
+    }
+
+    assert_eq!(sent.len(), 5);
+    assert!(sent.iter().all(|s| matches!(s, Sent::Mouse(0, x) if *x > 0)));
+}
+
+#[test]
+fn skipped_motion_does_not_add_up() {
+    let (mut kb, mut reports) = (Keyboard::new(), Reports::new());
+
+    // a second of motion while the other half sends
+    kb.mouse.handle_mouse_btn(&MAction::Move(Dir::Right), true);
+    for _ in 0..1000 {
+        kb.tick();
+        reports.skip(&mut kb);
+    }
+    kb.mouse.handle_mouse_btn(&MAction::Move(Dir::Right), false);
+
+    kb.tick();
+    reports.update(&mut kb);
+    assert!(take(&mut reports).is_empty());
+}
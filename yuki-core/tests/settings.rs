@@ -0,0 +1,95 @@
+use yuki_core::{
+    mouse::Speed,
+    settings::{Settings, Storage},
+};
+
+struct Ram {
+    bytes: Vec<u8>,
+    erases: usize,
+}
+
+impl Ram {
+    fn new(len: usize) -> Self {
+        Self { bytes: vec![0xFF; len], erases: 0 }
+    }
+}
+
+impl Storage for Ram {
+    fn read(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn erase(&mut self) -> Result<(), ()> {
+        self.bytes.fill(0xFF);
+        self.erases += 1;
+        Ok(())
+    }
+
+    fn program(&mut self, offset: usize, bytes: &[u8]) -> Result<(), ()> {
+        // flash can only clear bits
+        for (b, new) in self.bytes[offset..offset + bytes.len()].iter_mut().zip(bytes) {
+            *b &= new;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn defaults_when_empty() {
+    assert!(Settings::load(&Ram::new(64)) == Settings::new());
+}
+
+#[test]
+fn last_record_wins() {
+    let mut ram = Ram::new(64);
+
+    for layer in [3, 4, 0] {
+        Settings { default_layer: layer, ..Settings::new() }.store(&mut ram);
+        assert_eq!(Settings::load(&ram).default_layer, layer);
+    }
+    assert_eq!(ram.erases, 0);
+}
+
+#[test]
+fn erased_when_full() {
+    // room for four 16 byte records
+    let mut ram = Ram::new(64);
+
+    for layer in 0..5 {
+        let erased = Settings { default_layer: layer, ..Settings::new() }.store(&mut ram);
+        assert_eq!(erased, layer == 4);
+    }
+
+    assert_eq!(ram.erases, 1);
+    assert_eq!(Settings::load(&ram).default_layer, 4);
+}
+
+#[test]
+fn hold_tap_terms_are_saved() {
+    let mut ram = Ram::new(64);
+    let mut settings = Settings::new();
+    settings.hold_tap_terms[1] = 230;
+    settings.store(&mut ram);
+
+    assert!(Settings::load(&ram) == settings);
+}
+
+#[test]
+fn mouse_speed_is_saved() {
+    let mut ram = Ram::new(64);
+    Settings { mouse_speed: Speed::Turbo, ..Settings::new() }.store(&mut ram);
+
+    assert_eq!(Settings::load(&ram).mouse_speed, Speed::Turbo);
+}
+
+#[test]
+fn records_without_terms() {
+    // saved by a firmware that only had the default layer
+    let mut ram = Ram::new(64);
+    ram.program(0, &[0x59, 3]).unwrap();
+
+    let settings = Settings::load(&ram);
+    assert_eq!(settings.default_layer, 3);
+    assert_eq!(settings.hold_tap_terms, Settings::new().hold_tap_terms);
+    assert_eq!(settings.mouse_speed, Speed::Normal);
+}
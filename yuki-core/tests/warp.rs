@@ -0,0 +1,92 @@
+use {
+    yuki_core::{
+        keyboard::Keyboard,
+        layout::{WARP_BACK, WARP_CLICK, WARP_GRID_3, WARP_GRID_4},
+        warp::{AbsReport, ABS_MAX},
+    },
+    common::tap,
+};
+
+mod common;
+
+// (3, 9) holds the mouse layer, (1, 6) starts warping with a 3x3 grid
+// and (2, 6) with a 4x4 one
+fn warping(coord: (u8, u8)) -> Keyboard {
+    let mut kb = Keyboard::new();
+    kb.press(3, 9);
+    kb.tick();
+    tap(&mut kb, coord);
+    kb.release(3, 9);
+    kb.tick();
+    kb
+}
+
+fn pointer(kb: &Keyboard) -> (u16, u16) {
+    let r = kb.mouse.warp.report().unwrap();
+    (r.x, r.y)
+}
+
+#[test]
+fn cells_narrow_down() {
+    let mut kb = warping((1, 6));
+    assert!(kb.mouse.warp.is_active());
+    assert_eq!(kb.mouse.warp.report(), None);
+
+    let third = ABS_MAX / 3;
+    tap(&mut kb, WARP_GRID_3[2][0]);
+    assert_eq!(pointer(&kb), (third / 2, 2 * third + third / 2));
+    kb.mouse.warp.sent();
+
+    tap(&mut kb, WARP_GRID_3[0][2]);
+    assert_eq!(pointer(&kb), (2 * (third / 3) + third / 6, 2 * third + third / 6));
+    assert_eq!(kb.info().as_deref(), Some("W 2"));
+
+    tap(&mut kb, WARP_BACK);
+    assert_eq!(pointer(&kb), (third / 2, 2 * third + third / 2));
+
+    // none of it goes to the layout
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn four_by_four_grid() {
+    let mut kb = warping((2, 6));
+
+    tap(&mut kb, WARP_GRID_4[3][3]);
+    let quarter = ABS_MAX / 4;
+    assert_eq!(pointer(&kb), (3 * quarter + quarter / 2, 3 * quarter + quarter / 2));
+}
+
+#[test]
+fn click_stops_warping() {
+    let mut kb = warping((1, 6));
+    tap(&mut kb, WARP_GRID_3[1][1]);
+    kb.mouse.warp.sent();
+
+    let (i, j) = WARP_CLICK;
+    kb.press(i, j);
+    kb.tick();
+    assert_eq!(kb.mouse.warp.report().map(|r| r.buttons), Some(0x1));
+
+    kb.release(i, j);
+    kb.tick();
+    assert_eq!(kb.mouse.warp.report().map(|r| r.buttons), Some(0));
+    assert!(!kb.mouse.warp.is_active());
+    assert_eq!(kb.info(), None);
+    assert!(kb.keys.is_empty());
+}
+
+#[test]
+fn other_keys_stop_warping() {
+    let mut kb = warping((1, 6));
+
+    tap(&mut kb, (0, 8));
+    assert!(!kb.mouse.warp.is_active());
+    assert_eq!(kb.mouse.warp.report, AbsReport::default());
+    assert!(kb.keys.is_empty());
+
+    // and then keys type again, (0, 4) is P
+    kb.press(0, 4);
+    kb.tick();
+    assert!(!kb.keys.is_empty());
+}